@@ -1,7 +1,8 @@
 use std::{
-  collections::HashSet,
-  env, fs,
-  path::{Path, PathBuf},
+  collections::{HashMap, HashSet},
+  env, error, fmt, fs,
+  io::{self, Read, Write},
+  path::{Component, Path, PathBuf},
   sync::{
     mpsc::{channel, Sender},
     Arc, Mutex,
@@ -12,10 +13,104 @@ use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use crate::{
   generate::{GenerateOptions, Generator},
-  parse::Parser,
-  visit::Visit,
+  parse::{
+    ConstDefinition, ConstValue, FieldDefinition, FunctionDefinition, Parser, ThriftDocument,
+    ThriftType, TopDefinition, TypedefDefinition,
+  },
+  validate::{validate, validate_annotation_scopes},
+  visit::{walk_const_value, Visit},
 };
 
+/// 从标准输入读取时，错误信息中使用的文件名
+const STDIN_NAME: &str = "<stdin>";
+
+/// 存放 src_dir 之外的 include 文件的目录
+const EXTERNAL_DIR: &str = "_external";
+
+/// 编译失败的原因，`Display` 的输出与之前的错误信息保持一致
+#[derive(Debug)]
+pub enum CompileError {
+  /// out_dir 与 src_dir 相同或包含 src_dir，清空 out_dir 会删掉源文件
+  OverlappingDirs { src_dir: String, out_dir: String },
+  /// 读取源文件或写入生成的文件失败
+  Io { file: String, error: io::Error },
+  /// IDL 语法错误
+  Parse { file: String, message: String },
+  /// 语法正确但没有通过 `validate` 的检查
+  Validation {
+    file: String,
+    diagnostics: Vec<String>,
+  },
+  /// 合并到同一个文件时，不同文件中出现了同名的定义
+  DuplicateDefinition {
+    name: String,
+    first_file: String,
+    second_file: String,
+  },
+}
+
+impl fmt::Display for CompileError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CompileError::OverlappingDirs { src_dir, out_dir } => write!(
+        f,
+        "Compiler failed: out_dir {} is the same as or contains src_dir {}.",
+        out_dir, src_dir
+      ),
+      CompileError::Io { file, error } => write!(f, "Compiler failed: {}. {}", file, error),
+      CompileError::Parse { file, message } => {
+        write!(f, "Compiler failed: {}. {}", file, message)
+      }
+      CompileError::Validation { file, diagnostics } => {
+        write!(f, "Compiler failed: {}. {}", file, diagnostics.join("; "))
+      }
+      CompileError::DuplicateDefinition {
+        name,
+        first_file,
+        second_file,
+      } => write!(
+        f,
+        "Compiler failed: {}. Duplicate definition `{}`, already defined in {}",
+        second_file, name, first_file
+      ),
+    }
+  }
+}
+
+impl error::Error for CompileError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      CompileError::Io { error, .. } => Some(error),
+      _ => None,
+    }
+  }
+}
+
+impl CompileError {
+  fn io(file: impl fmt::Display, error: io::Error) -> Self {
+    CompileError::Io {
+      file: file.to_string(),
+      error,
+    }
+  }
+
+  fn parse(file: impl fmt::Display, message: String) -> Self {
+    CompileError::Parse {
+      file: file.to_string(),
+      message,
+    }
+  }
+}
+
+/// 编译时多个线程共享的状态
+#[derive(Default)]
+struct CompileState {
+  /// 已经编译过的源文件
+  seen: HashSet<PathBuf>,
+  /// 本次生成的文件，编译结束后 out_dir 中的其他文件会被删除
+  outputs: HashSet<PathBuf>,
+}
+
 pub struct Compiler {
   input: Vec<String>,
   src_dir: String,
@@ -38,18 +133,24 @@ impl Compiler {
     }
   }
 
-  pub fn compile(&self) -> Result<(), String> {
+  pub fn compile(&self) -> Result<(), CompileError> {
     let thread_pool = Arc::new(ThreadPoolBuilder::new().build().unwrap());
-    let (err_sender, err_receiver) = channel::<String>();
-    let seen = Arc::new(Mutex::new(vec![]));
+    let (err_sender, err_receiver) = channel::<CompileError>();
+    let state = Arc::new(Mutex::new(CompileState::default()));
 
-    let src_dir = resolve_path(&self.src_dir).unwrap();
+    let src_dir = normalize_path(Path::new(&resolve_path(&self.src_dir).unwrap()))
+      .to_string_lossy()
+      .to_string();
     let src_dir_path = Path::new(&src_dir);
     let out_dir: String = resolve_path(&self.out_dir).unwrap();
     let out_dir_path = Path::new(&out_dir);
 
-    if out_dir_path.exists() {
-      fs::remove_dir_all(out_dir_path).unwrap();
+    // 清理 out_dir 之前先确认不会删掉源文件
+    let canonical_src_dir = canonicalize_path(src_dir_path);
+    let canonical_out_dir = canonicalize_path(out_dir_path);
+
+    if canonical_src_dir.starts_with(canonical_out_dir) {
+      return Err(CompileError::OverlappingDirs { src_dir, out_dir });
     }
 
     self.input.iter().for_each(|file| {
@@ -62,7 +163,7 @@ impl Compiler {
       Self::compile_file(
         thread_pool.clone(),
         err_sender.clone(),
-        seen.clone(),
+        state.clone(),
         file.to_string_lossy().to_string(),
         src_dir.clone(),
         out_dir.clone(),
@@ -76,13 +177,223 @@ impl Compiler {
       return Err(err);
     }
 
+    // 内容没有变化的文件不会重新写入，最后再删除不再生成的文件
+    let outputs = &state.lock().unwrap().outputs;
+    remove_stale_outputs(out_dir_path, outputs).map_err(|err| CompileError::io(&out_dir, err))
+  }
+
+  /// 从 `reader` 读取 IDL，生成的 TS 代码写入 `writer`，不会编译依赖，include 相对当前目录解析
+  pub fn compile_io(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    options: GenerateOptions,
+  ) -> Result<(), CompileError> {
+    let mut code = String::new();
+    reader
+      .read_to_string(&mut code)
+      .map_err(|err| CompileError::io(STDIN_NAME, err))?;
+
+    let mut ast = Parser::new(&code)
+      .parse()
+      .map_err(|err| CompileError::parse(STDIN_NAME, err))?;
+
+    let diagnostics = collect_diagnostics(&ast, &options);
+
+    if !diagnostics.is_empty() {
+      return Err(CompileError::Validation {
+        file: STDIN_NAME.to_string(),
+        diagnostics,
+      });
+    }
+
+    let file = env::current_dir()
+      .unwrap()
+      .join(STDIN_NAME)
+      .to_string_lossy()
+      .to_string();
+    let include_aliases = resolve_include_aliases(&file, &mut ast);
+    let ts_code = Generator::new(&mut ast)
+      .include_aliases(include_aliases)
+      .source_file(STDIN_NAME)
+      .build(options);
+
+    writer
+      .write_all(ts_code.as_bytes())
+      .map_err(|err| CompileError::io(STDIN_NAME, err))
+  }
+
+  /// 从标准输入读取 IDL，生成的 TS 代码输出到标准输出
+  pub fn compile_stdin(options: GenerateOptions) -> Result<(), CompileError> {
+    Self::compile_io(io::stdin().lock(), io::stdout().lock(), options)
+  }
+
+  /// 只编译一个文件，返回生成的 TS 代码和其中 include 的路径，不会写入文件，也不会编译依赖
+  ///
+  /// `file` 为相对 `src_dir` 的路径，校验失败时每个问题对应一个错误
+  pub fn compile_one(&self, file: &str) -> Result<(String, Vec<String>), Vec<CompileError>> {
+    let src_dir = normalize_path(Path::new(&resolve_path(&self.src_dir).unwrap()))
+      .to_string_lossy()
+      .to_string();
+    let file = normalize_path(&Path::new(&src_dir).join(file))
+      .to_string_lossy()
+      .to_string();
+    let relative_path = out_relative_path(Path::new(&file), Path::new(&src_dir));
+    let relative_file = &relative_path.to_string_lossy().to_string();
+
+    let code =
+      fs::read_to_string(&file).map_err(|err| vec![CompileError::io(relative_file, err)])?;
+    let mut ast = Parser::new(&code)
+      .parse()
+      .map_err(|err| vec![CompileError::parse(relative_file, err)])?;
+
+    let diagnostics = collect_diagnostics(&ast, &self.options);
+
+    if !diagnostics.is_empty() {
+      return Err(
+        diagnostics
+          .into_iter()
+          .map(|diagnostic| CompileError::Validation {
+            file: relative_file.to_string(),
+            diagnostics: vec![diagnostic],
+          })
+          .collect(),
+      );
+    }
+
+    // 按照源码中的顺序返回依赖
+    let deps = ast
+      .body
+      .iter()
+      .filter_map(|definition| match definition {
+        TopDefinition::Include(include_definition) => Some(include_definition.path.value.clone()),
+        _ => None,
+      })
+      .collect();
+
+    let include_aliases = resolve_include_aliases(&file, &mut ast);
+    let include_paths = resolve_include_paths(&file, &src_dir, &mut ast);
+    let ts_code = Generator::new(&mut ast)
+      .include_aliases(include_aliases)
+      .include_paths(include_paths)
+      .source_file(relative_file)
+      .build(self.options.clone());
+
+    Ok((ts_code, deps))
+  }
+
+  /// 将所有输入及其依赖合并生成到一个文件中，`out_file` 为相对 `out_dir` 的路径
+  pub fn bundle(&self, out_file: &str) -> Result<(), CompileError> {
+    let src_dir = resolve_path(&self.src_dir).unwrap();
+    let src_dir_path = canonicalize_path(Path::new(&src_dir));
+    let out_dir = resolve_path(&self.out_dir).unwrap();
+
+    let mut seen = vec![];
+    let mut definitions = vec![];
+
+    for file in &self.input {
+      Self::bundle_file(
+        &src_dir_path.join(file),
+        &src_dir_path,
+        &self.options,
+        &mut seen,
+        &mut definitions,
+      )?;
+    }
+
+    let mut document = ThriftDocument {
+      body: sort_definitions(
+        definitions
+          .into_iter()
+          .map(|(_, definition)| definition)
+          .collect(),
+      ),
+    };
+    let ts_code = Generator::new(&mut document).build(self.options.clone());
+
+    let out_file = PathBuf::from(&out_dir).join(out_file);
+    write_if_changed(&out_file, &ts_code).map_err(|err| CompileError::io(out_file.display(), err))
+  }
+
+  /// 先收集依赖中的定义，再收集当前文件的定义，`definitions` 中同时记录定义所在的文件，
+  /// 不同文件中出现同名的定义时返回错误
+  fn bundle_file(
+    file: &Path,
+    src_dir: &Path,
+    options: &GenerateOptions,
+    seen: &mut Vec<PathBuf>,
+    definitions: &mut Vec<(String, TopDefinition)>,
+  ) -> Result<(), CompileError> {
+    let file = canonicalize_path(file);
+
+    if seen.contains(&file) {
+      return Ok(());
+    }
+
+    seen.push(file.clone());
+
+    let relative_file = file.strip_prefix(src_dir).unwrap_or(&file).display();
+    let code = fs::read_to_string(&file).map_err(|err| CompileError::io(&relative_file, err))?;
+    let mut ast = timed("parse", &relative_file.to_string(), || {
+      Parser::new(&code).parse()
+    })
+    .map_err(|err| CompileError::parse(&relative_file, err))?;
+
+    let diagnostics = timed("validate", &relative_file.to_string(), || {
+      collect_diagnostics(&ast, options)
+    });
+
+    if !diagnostics.is_empty() {
+      return Err(CompileError::Validation {
+        file: relative_file.to_string(),
+        diagnostics,
+      });
+    }
+
+    for definition in &ast.body {
+      if let TopDefinition::Include(include_definition) = definition {
+        let dep_file = file.parent().unwrap().join(&include_definition.path.value);
+        Self::bundle_file(&dep_file, src_dir, options, seen, definitions)?;
+      }
+    }
+
+    let mut qualifiers = resolve_include_aliases(&file.to_string_lossy(), &mut ast)
+      .into_values()
+      .collect::<HashSet<_>>();
+    let mut deps_visitor = DepsVisitor::new();
+    deps_visitor.visit_document(&mut ast);
+    qualifiers.extend(deps_visitor.deps.iter().map(|dep| {
+      Path::new(dep)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+    }));
+
+    StripQualifierVisitor { qualifiers }.visit_document(&mut ast);
+
+    for definition in ast.body {
+      let Some(name) = definition.name() else {
+        continue;
+      };
+
+      if let Some((first_file, _)) = definitions.iter().find(|(_, v)| v.name() == Some(name)) {
+        return Err(CompileError::DuplicateDefinition {
+          name: name.to_string(),
+          first_file: first_file.clone(),
+          second_file: relative_file.to_string(),
+        });
+      }
+
+      definitions.push((relative_file.to_string(), definition));
+    }
+
     Ok(())
   }
 
   fn compile_file(
     thread_pool: Arc<ThreadPool>,
-    err_sender: Sender<String>,
-    seen: Arc<Mutex<Vec<String>>>,
+    err_sender: Sender<CompileError>,
+    state: Arc<Mutex<CompileState>>,
     file: String,
     src_dir: String,
     out_dir: String,
@@ -91,41 +402,90 @@ impl Compiler {
     let cloned_thread_pool = thread_pool.clone();
 
     thread_pool.spawn(move || {
-      let mut seen_data = seen.lock().unwrap();
+      // 去掉 include 路径中的 `..`，同一个文件只编译一次
+      let file = normalize_path(Path::new(&file))
+        .to_string_lossy()
+        .to_string();
 
-      if seen_data.contains(&file) {
+      if !state
+        .lock()
+        .unwrap()
+        .seen
+        .insert(seen_key(Path::new(&file)))
+      {
         return;
       }
 
-      seen_data.push(file.clone());
-      drop(seen_data);
+      let relative_path = out_relative_path(Path::new(&file), Path::new(&src_dir));
+      let relative_file = &relative_path.to_string_lossy().to_string();
 
-      let code = fs::read_to_string(&file).unwrap();
-      let mut relative_file = file.strip_prefix(&src_dir).unwrap();
-
-      if relative_file.starts_with("/") {
-        relative_file = relative_file.strip_prefix("/").unwrap();
-      }
+      let code = match fs::read_to_string(&file) {
+        Ok(code) => code,
+        Err(err) => {
+          err_sender
+            .send(CompileError::io(relative_file, err))
+            .unwrap();
+          return;
+        }
+      };
 
       // 解析 IDL 代码
-      let mut ast = match Parser::new(&code).parse() {
+      let mut ast = match timed("parse", relative_file, || Parser::new(&code).parse()) {
         Ok(ast) => ast,
         Err(err) => {
           err_sender
-            .send(format!("Compiler failed: {}. {}", relative_file, err))
+            .send(CompileError::parse(relative_file, err))
             .unwrap();
           return;
         }
       };
 
+      let diagnostics = timed("validate", relative_file, || {
+        collect_diagnostics(&ast, &options)
+      });
+
+      if !diagnostics.is_empty() {
+        err_sender
+          .send(CompileError::Validation {
+            file: relative_file.to_string(),
+            diagnostics,
+          })
+          .unwrap();
+        return;
+      }
+
+      let is_empty = options.skip_empty_output
+        && !options.reexport_includes
+        && ast
+          .body
+          .iter()
+          .all(|definition| definition.name().is_none());
+
       // 生成 TS 代码
-      let ts_code = Generator::new(&mut ast).build(options.clone());
+      let include_aliases = resolve_include_aliases(&file, &mut ast);
+      let include_paths = resolve_include_paths(&file, &src_dir, &mut ast);
+      let ts_code = timed("generate", relative_file, || {
+        Generator::new(&mut ast)
+          .include_aliases(include_aliases)
+          .include_paths(include_paths)
+          .source_file(relative_file)
+          .build(options.clone())
+      });
 
       // 写入文件
-      let mut out_file = PathBuf::from(&out_dir).join(relative_file);
-      out_file.set_extension("ts");
-      fs::create_dir_all(out_file.parent().unwrap()).unwrap();
-      fs::write(&out_file, ts_code).unwrap();
+      if !is_empty {
+        let mut out_file = PathBuf::from(&out_dir).join(relative_file);
+        out_file.set_extension("ts");
+        let written = write_if_changed(&out_file, &ts_code);
+        state.lock().unwrap().outputs.insert(out_file);
+
+        if let Err(err) = written {
+          err_sender
+            .send(CompileError::io(relative_file, err))
+            .unwrap();
+          return;
+        }
+      }
 
       // 分析依赖，继续解析
       let mut deps_visitor = DepsVisitor::new();
@@ -142,7 +502,7 @@ impl Compiler {
         Self::compile_file(
           cloned_thread_pool.clone(),
           err_sender.clone(),
-          seen.clone(),
+          state.clone(),
           dep_file,
           src_dir.clone(),
           out_dir.clone(),
@@ -171,6 +531,446 @@ impl Visit for DepsVisitor {
   }
 }
 
+/// 收集引用到的类型名称，如 `a.A`、`GetDataReq`
+struct TypeRefsVisitor {
+  pub refs: Vec<String>,
+}
+
+impl TypeRefsVisitor {
+  pub fn new() -> Self {
+    Self { refs: vec![] }
+  }
+
+  /// 引用 include 类型时使用的前缀，如 `a.A` 中的 `a`
+  pub fn qualifiers(&self) -> HashSet<String> {
+    self
+      .refs
+      .iter()
+      .filter_map(|name| name.rsplit_once('.'))
+      .map(|(qualifier, _)| qualifier.to_string())
+      .collect()
+  }
+
+  fn collect(&mut self, thrift_type: &ThriftType) {
+    match thrift_type {
+      ThriftType::List(ty) => self.collect(ty),
+      ThriftType::Map(key_ty, value_ty) => {
+        self.collect(key_ty);
+        self.collect(value_ty);
+      }
+      ThriftType::Identifier(id) => {
+        if !self.refs.contains(&id.value) {
+          self.refs.push(id.value.clone());
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+impl Visit for TypeRefsVisitor {
+  fn visit_struct_field_definition(&mut self, field_definition: &mut FieldDefinition) {
+    self.collect(&field_definition.field_type);
+  }
+
+  fn visit_typedef_definition(&mut self, typedef_definition: &mut TypedefDefinition) {
+    self.collect(&typedef_definition.field_type);
+  }
+
+  fn visit_const_definition(&mut self, const_definition: &mut ConstDefinition) {
+    self.collect(&const_definition.field_type);
+  }
+
+  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {
+    self.collect(&function_definition.return_type);
+
+    for field in function_definition
+      .fields
+      .iter()
+      .chain(&function_definition.throws)
+    {
+      self.collect(&field.field_type);
+    }
+  }
+}
+
+/// 去掉类型引用和常量值中 include 的前缀，合并到同一个文件后 `a.A` 直接引用 `A`，
+/// `a.ItemType.Normal` 直接引用 `ItemType.Normal`
+struct StripQualifierVisitor {
+  /// 当前文件中 include 的前缀，包括文件名和 namespace 别名
+  qualifiers: HashSet<String>,
+}
+
+impl StripQualifierVisitor {
+  fn strip(thrift_type: &mut ThriftType) {
+    match thrift_type {
+      ThriftType::List(ty) => Self::strip(ty),
+      ThriftType::Map(key_ty, value_ty) => {
+        Self::strip(key_ty);
+        Self::strip(value_ty);
+      }
+      ThriftType::Identifier(id) => {
+        if let Some((_, name)) = id.value.rsplit_once('.') {
+          id.value = name.to_string();
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn visit_fields(&mut self, fields: &mut [FieldDefinition]) {
+    for field in fields {
+      self.visit_struct_field_definition(field);
+    }
+  }
+}
+
+impl Visit for StripQualifierVisitor {
+  fn visit_struct_field_definition(&mut self, field_definition: &mut FieldDefinition) {
+    Self::strip(&mut field_definition.field_type);
+
+    if let Some(default_value) = &mut field_definition.default_value {
+      self.visit_default_value(default_value);
+    }
+  }
+
+  fn visit_typedef_definition(&mut self, typedef_definition: &mut TypedefDefinition) {
+    Self::strip(&mut typedef_definition.field_type);
+  }
+
+  fn visit_const_definition(&mut self, const_definition: &mut ConstDefinition) {
+    Self::strip(&mut const_definition.field_type);
+    self.visit_const_value(&mut const_definition.value);
+  }
+
+  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {
+    Self::strip(&mut function_definition.return_type);
+    self.visit_fields(&mut function_definition.fields);
+    self.visit_fields(&mut function_definition.throws);
+  }
+
+  /// 常量值中的标识符可能带有多段，如 `a.ItemType.Normal`，只去掉第一段是 include 前缀的情况
+  fn visit_const_value(&mut self, const_value: &mut ConstValue) {
+    if let ConstValue::Identifier(id) = const_value {
+      if let Some((qualifier, name)) = id.value.split_once('.') {
+        if self.qualifiers.contains(qualifier) {
+          id.value = name.to_string();
+        }
+      }
+    }
+
+    walk_const_value(self, const_value);
+  }
+}
+
+/// 内容与已有的文件相同时不写入，保留文件的修改时间，避免触发下游的监听和重新构建
+fn write_if_changed(file: &Path, code: &str) -> io::Result<()> {
+  if fs::read(file).is_ok_and(|old_code| old_code == code.as_bytes()) {
+    return Ok(());
+  }
+
+  fs::create_dir_all(file.parent().unwrap())?;
+  fs::write(file, code)
+}
+
+/// 删除 `dir` 中不属于 `outputs` 的文件，以及删除后变为空的目录
+fn remove_stale_outputs(dir: &Path, outputs: &HashSet<PathBuf>) -> io::Result<()> {
+  if !dir.is_dir() {
+    return Ok(());
+  }
+
+  for entry in fs::read_dir(dir)? {
+    let entry = entry?;
+    let path = entry.path();
+
+    // file_type 不会跟随符号链接，指向目录的链接与文件一样只删除链接本身
+    if entry.file_type()?.is_dir() {
+      remove_stale_outputs(&path, outputs)?;
+
+      if fs::read_dir(&path)?.next().is_none() {
+        fs::remove_dir(&path)?;
+      }
+    } else if !outputs.contains(&path) {
+      fs::remove_file(&path)?;
+    }
+  }
+
+  Ok(())
+}
+
+/// `validate` 的结果，配置了 `known_annotation_scopes` 时还会检查注解的作用域
+fn collect_diagnostics(ast: &ThriftDocument, options: &GenerateOptions) -> Vec<String> {
+  let mut diagnostics = validate(ast);
+
+  if let Some(scopes) = &options.known_annotation_scopes {
+    diagnostics.extend(validate_annotation_scopes(ast, scopes));
+  }
+
+  diagnostics
+}
+
+/// 按照依赖关系排序，被引用的定义排在前面，循环引用时保持原有顺序
+fn sort_definitions(mut definitions: Vec<TopDefinition>) -> Vec<TopDefinition> {
+  let refs = definitions
+    .iter_mut()
+    .map(|definition| {
+      let mut type_refs_visitor = TypeRefsVisitor::new();
+      type_refs_visitor.visit_definition(definition);
+      type_refs_visitor.refs
+    })
+    .collect::<Vec<_>>();
+  let mut names = HashMap::new();
+
+  for (index, definition) in definitions.iter().enumerate() {
+    if let Some(name) = definition.name() {
+      names.entry(name.to_string()).or_insert(index);
+    }
+  }
+
+  // 使用显式的栈做深度优先遍历，引用链很长时也不会栈溢出。
+  // 栈中保存定义的下标以及下一个要访问的引用，入栈时即标记为已访问，循环引用时不会重复访问
+  let mut order = vec![];
+  let mut visited = vec![false; definitions.len()];
+
+  for root in 0..definitions.len() {
+    if visited[root] {
+      continue;
+    }
+
+    visited[root] = true;
+    let mut stack = vec![(root, 0)];
+
+    while let Some(top) = stack.last_mut() {
+      let (index, next) = *top;
+
+      match refs[index].get(next) {
+        Some(name) => {
+          top.1 += 1;
+
+          if let Some(&dep_index) = names.get(name) {
+            if !visited[dep_index] {
+              visited[dep_index] = true;
+              stack.push((dep_index, 0));
+            }
+          }
+        }
+        None => {
+          stack.pop();
+          order.push(index);
+        }
+      }
+    }
+  }
+
+  let mut definitions = definitions.into_iter().map(Some).collect::<Vec<_>>();
+
+  order
+    .into_iter()
+    .map(|index| definitions[index].take().unwrap())
+    .collect()
+}
+
+/// 被引用文件的 namespace 可能与文件名不同，而类型引用使用的是 namespace，
+/// 此时需要用 namespace 作为导入名称，才能和类型引用对应上
+fn resolve_include_aliases(file: &str, ast: &mut ThriftDocument) -> HashMap<String, String> {
+  let mut type_refs_visitor = TypeRefsVisitor::new();
+  type_refs_visitor.visit_document(ast);
+  let qualifiers = type_refs_visitor.qualifiers();
+
+  let mut deps_visitor = DepsVisitor::new();
+  deps_visitor.visit_document(ast);
+
+  let mut include_aliases = HashMap::new();
+
+  for dep in deps_visitor.deps {
+    let stem = Path::new(&dep)
+      .file_stem()
+      .unwrap_or_default()
+      .to_string_lossy()
+      .to_string();
+
+    if qualifiers.contains(&stem) {
+      continue;
+    }
+
+    // 读取或解析失败时不处理，编译该依赖时会报告错误
+    let dep_file = Path::new(file).parent().unwrap().join(&dep);
+    let Ok(code) = fs::read_to_string(dep_file) else {
+      continue;
+    };
+    let Ok(dep_ast) = Parser::new(&code).parse() else {
+      continue;
+    };
+
+    if let Some(namespace) = ts_namespace(&dep_ast) {
+      if qualifiers.contains(&namespace) {
+        include_aliases.insert(dep, namespace);
+      }
+    }
+  }
+
+  include_aliases
+}
+
+/// include 对应的导入路径，如 `./b`、`../common/base`，与输出文件的目录结构保持一致
+fn resolve_include_paths(
+  file: &str,
+  src_dir: &str,
+  ast: &mut ThriftDocument,
+) -> HashMap<String, String> {
+  let mut deps_visitor = DepsVisitor::new();
+  deps_visitor.visit_document(ast);
+
+  let out_path = out_relative_path(Path::new(file), Path::new(src_dir));
+  let out_parent = out_path.parent().unwrap();
+
+  deps_visitor
+    .deps
+    .into_iter()
+    .map(|dep| {
+      let dep_file = normalize_path(&Path::new(file).parent().unwrap().join(&dep));
+      let mut dep_out_path = out_relative_path(&dep_file, Path::new(src_dir));
+      dep_out_path.set_extension("");
+
+      let import_path = relative_path(out_parent, &dep_out_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+      let import_path = if import_path.starts_with("..") {
+        import_path
+      } else {
+        format!("./{}", import_path)
+      };
+
+      (dep, import_path)
+    })
+    .collect()
+}
+
+/// 依次取 ts、js、* 作用域的 namespace，`a.b.c` 这种形式取最后一段
+fn ts_namespace(document: &ThriftDocument) -> Option<String> {
+  ["ts", "js", "*"].iter().find_map(|scope| {
+    document
+      .body
+      .iter()
+      .find_map(|definition| match definition {
+        TopDefinition::Namespace(namespace) if namespace.scope.value == *scope => {
+          Some(namespace.name.value.rsplit('.').next().unwrap().to_string())
+        }
+        _ => None,
+      })
+  })
+}
+
+/// 执行某个编译阶段，开启 `trace` feature 时会记录对应的 span 和耗时
+fn timed<T>(phase: &'static str, file: &str, f: impl FnOnce() -> T) -> T {
+  #[cfg(feature = "trace")]
+  {
+    let span = tracing::info_span!("compile_phase", phase, file);
+    let _enter = span.enter();
+    let start = std::time::Instant::now();
+    let ret = f();
+
+    tracing::info!(
+      phase,
+      file,
+      duration_us = start.elapsed().as_micros() as u64,
+      "phase finished"
+    );
+
+    ret
+  }
+
+  #[cfg(not(feature = "trace"))]
+  {
+    let _ = (phase, file);
+    f()
+  }
+}
+
+/// 解析符号链接等得到真实路径，不存在的部分原样拼接到最近一级存在的父目录之后
+fn canonicalize_path(path: &Path) -> PathBuf {
+  if let Ok(canonical_path) = fs::canonicalize(path) {
+    return canonical_path;
+  }
+
+  match (path.parent(), path.file_name()) {
+    (Some(parent), Some(file_name)) => canonicalize_path(parent).join(file_name),
+    _ => path.to_path_buf(),
+  }
+}
+
+/// 判断是否为同一个文件使用的路径，符号链接、大小写不敏感的文件系统上不同写法的路径都会得到相同的结果
+fn seen_key(file: &Path) -> PathBuf {
+  canonicalize_path(&normalize_path(file))
+}
+
+/// 不访问文件系统，直接去掉路径中的 `.` 和 `..`
+fn normalize_path(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if !normalized.pop() {
+          normalized.push(component);
+        }
+      }
+      _ => normalized.push(component),
+    }
+  }
+
+  normalized
+}
+
+/// 从 `from_dir` 到 `to` 的相对路径，两者都需要是规范化后的路径
+fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+  let from_components = from_dir.components().collect::<Vec<_>>();
+  let to_components = to.components().collect::<Vec<_>>();
+  let common_len = from_components
+    .iter()
+    .zip(&to_components)
+    .take_while(|(a, b)| a == b)
+    .count();
+
+  let mut path = PathBuf::new();
+
+  for _ in common_len..from_components.len() {
+    path.push("..");
+  }
+
+  for component in &to_components[common_len..] {
+    path.push(component);
+  }
+
+  path
+}
+
+/// 文件相对 `out_dir` 的输出路径，`src_dir` 之外的文件放到 `_external` 目录下，
+/// 如 `src_dir/../common/base.thrift` 对应 `_external/common/base.thrift`。
+/// 向上超过一层时在路径中记录层数，如 `src_dir/../../common/base.thrift` 对应
+/// `_external/__up2/common/base.thrift`，避免与上一层的同名文件冲突
+fn out_relative_path(file: &Path, src_dir: &Path) -> PathBuf {
+  if let Ok(relative_file) = file.strip_prefix(src_dir) {
+    return relative_file.to_path_buf();
+  }
+
+  let relative_file = relative_path(src_dir, file);
+  let depth = relative_file
+    .components()
+    .take_while(|component| *component == Component::ParentDir)
+    .count();
+  let path = relative_file.components().skip(depth).collect::<PathBuf>();
+
+  if depth > 1 {
+    Path::new(EXTERNAL_DIR)
+      .join(format!("__up{}", depth))
+      .join(path)
+  } else {
+    Path::new(EXTERNAL_DIR).join(path)
+  }
+}
+
 fn resolve_path(mut path: &str) -> std::io::Result<String> {
   if Path::new(path).is_relative() {
     if path.starts_with("./") {
@@ -184,3 +984,175 @@ fn resolve_path(mut path: &str) -> std::io::Result<String> {
     Ok(path.to_string())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::{env, path::Path};
+
+  use super::{out_relative_path, remove_stale_outputs, seen_key, sort_definitions};
+  use crate::parse::Parser;
+
+  #[test]
+  fn test_seen_key() {
+    let key = seen_key(Path::new("tests/fixtures/compiler/a.thrift"));
+
+    for path in [
+      "./tests/fixtures/compiler/a.thrift",
+      "tests/fixtures/compiler/./out/../a.thrift",
+    ] {
+      assert_eq!(seen_key(Path::new(path)), key);
+    }
+
+    let absolute_path = env::current_dir()
+      .unwrap()
+      .join("tests/fixtures/compiler/a.thrift");
+    assert_eq!(seen_key(&absolute_path), key);
+    assert_ne!(seen_key(Path::new("tests/fixtures/compiler/b.thrift")), key);
+  }
+
+  #[test]
+  fn test_out_relative_path() {
+    let src_dir = Path::new("/repo/idl/src");
+
+    assert_eq!(
+      out_relative_path(Path::new("/repo/idl/src/sub/a.thrift"), src_dir),
+      Path::new("sub/a.thrift")
+    );
+    assert_eq!(
+      out_relative_path(Path::new("/repo/idl/x/a.thrift"), src_dir),
+      Path::new("_external/x/a.thrift")
+    );
+    assert_eq!(
+      out_relative_path(Path::new("/repo/x/a.thrift"), src_dir),
+      Path::new("_external/__up2/x/a.thrift")
+    );
+  }
+
+  #[test]
+  fn test_sort_definitions_long_chain() {
+    // 每个 struct 引用下一个，递归遍历时引用链的长度就是递归深度
+    let count = 50_000;
+    let mut idl = String::new();
+    for i in 0..count {
+      idl.push_str(&format!("struct S{} {{\n  1: S{} next\n}}\n", i, i + 1));
+    }
+    let document = Parser::new(&idl).parse().unwrap();
+
+    let names = sort_definitions(document.body)
+      .iter()
+      .map(|definition| definition.name().unwrap().to_string())
+      .collect::<Vec<_>>();
+
+    assert_eq!(names.len(), count);
+    assert_eq!(names[0], format!("S{}", count - 1));
+    assert_eq!(names[count - 1], "S0");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_seen_key_symlink() {
+    let link = env::temp_dir().join(format!("thrift_parser_seen_key_{}", std::process::id()));
+    let _ = std::fs::remove_file(&link);
+    std::os::unix::fs::symlink(
+      env::current_dir().unwrap().join("tests/fixtures/compiler"),
+      &link,
+    )
+    .unwrap();
+
+    let key = seen_key(&link.join("a.thrift"));
+    std::fs::remove_file(&link).unwrap();
+
+    assert_eq!(key, seen_key(Path::new("tests/fixtures/compiler/a.thrift")));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_remove_stale_outputs_keeps_symlink_target() {
+    let root = env::temp_dir().join(format!("thrift_parser_stale_{}", std::process::id()));
+    let out_dir = root.join("out");
+    let target_dir = root.join("target");
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&out_dir).unwrap();
+    std::fs::create_dir_all(&target_dir).unwrap();
+    std::fs::write(target_dir.join("keep.ts"), "").unwrap();
+    std::os::unix::fs::symlink(&target_dir, out_dir.join("link")).unwrap();
+
+    remove_stale_outputs(&out_dir, &Default::default()).unwrap();
+
+    assert!(target_dir.join("keep.ts").exists());
+    assert!(std::fs::symlink_metadata(out_dir.join("link")).is_err());
+    std::fs::remove_dir_all(&root).unwrap();
+  }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod trace_tests {
+  use std::sync::{Arc, Mutex};
+
+  use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    subscriber::with_default,
+    Event, Metadata, Subscriber,
+  };
+
+  use super::timed;
+
+  #[derive(Default)]
+  struct Collector {
+    records: Arc<Mutex<Vec<String>>>,
+  }
+
+  struct FieldsVisitor<'a>(&'a mut String);
+
+  impl<'a> Visit for FieldsVisitor<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+      self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+  }
+
+  impl Subscriber for Collector {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+      true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+      let mut record = format!("span {}", span.metadata().name());
+      span.record(&mut FieldsVisitor(&mut record));
+      self.records.lock().unwrap().push(record);
+      Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+      let mut record = "event".to_string();
+      event.record(&mut FieldsVisitor(&mut record));
+      self.records.lock().unwrap().push(record);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+  }
+
+  #[test]
+  fn test_timed_emits_spans() {
+    let collector = Collector::default();
+    let records = collector.records.clone();
+
+    let ret = with_default(collector, || timed("parse", "service.thrift", || 1 + 1));
+    assert_eq!(ret, 2);
+
+    let records = records.lock().unwrap();
+    assert_eq!(
+      records[0],
+      "span compile_phase phase=\"parse\" file=\"service.thrift\""
+    );
+    assert!(records[1].starts_with(
+      "event message=phase finished phase=\"parse\" file=\"service.thrift\" duration_us="
+    ));
+  }
+}
@@ -0,0 +1,427 @@
+use crate::parse::{
+  EnumDefinition, FieldDefinition, FunctionDefinition, Requiredness, SenumDefinition,
+  ServiceDefinition, ThriftDocument, TopDefinition,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaChangeKind {
+  DefinitionAdded,
+  DefinitionRemoved,
+  /// 同名定义的种类发生了变化，如 struct 变成了 enum
+  DefinitionKindChanged,
+  TypedefChanged,
+  FieldAdded,
+  FieldRemoved,
+  FieldTypeChanged,
+  FieldIdChanged,
+  FieldRequirednessChanged,
+  EnumMemberAdded,
+  EnumMemberRemoved,
+  EnumValueChanged,
+  FunctionAdded,
+  FunctionRemoved,
+  FunctionSignatureChanged,
+}
+
+/// 两个版本之间的一处差异，`path` 形如 `GetDataReq.status`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+  pub kind: SchemaChangeKind,
+  pub path: String,
+  pub breaking: bool,
+}
+
+impl SchemaChange {
+  fn new(kind: SchemaChangeKind, path: String, breaking: bool) -> Self {
+    Self {
+      kind,
+      path,
+      breaking,
+    }
+  }
+}
+
+/// 比较两个版本的文档，按照 thrift 的兼容性规则标记每处差异是否为破坏性变更
+pub fn diff(old: &ThriftDocument, new: &ThriftDocument) -> Vec<SchemaChange> {
+  let mut changes = vec![];
+
+  for old_definition in &old.body {
+    let Some(name) = old_definition.name() else {
+      continue;
+    };
+
+    match new
+      .body
+      .iter()
+      .find(|definition| definition.name() == Some(name))
+    {
+      Some(new_definition) => {
+        diff_definition(old, new, old_definition, new_definition, &mut changes)
+      }
+      None => changes.push(SchemaChange::new(
+        SchemaChangeKind::DefinitionRemoved,
+        name.to_string(),
+        true,
+      )),
+    }
+  }
+
+  for new_definition in &new.body {
+    let Some(name) = new_definition.name() else {
+      continue;
+    };
+
+    if !old
+      .body
+      .iter()
+      .any(|definition| definition.name() == Some(name))
+    {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::DefinitionAdded,
+        name.to_string(),
+        false,
+      ));
+    }
+  }
+
+  changes
+}
+
+fn diff_definition(
+  old_document: &ThriftDocument,
+  new_document: &ThriftDocument,
+  old: &TopDefinition,
+  new: &TopDefinition,
+  changes: &mut Vec<SchemaChange>,
+) {
+  match (old, new) {
+    (TopDefinition::Struct(old), TopDefinition::Struct(new))
+    | (TopDefinition::Exception(old), TopDefinition::Exception(new)) => diff_fields(
+      old_document,
+      new_document,
+      &old.name.value,
+      &old.fields,
+      &new.fields,
+      changes,
+    ),
+    (TopDefinition::Union(old), TopDefinition::Union(new)) => diff_fields(
+      old_document,
+      new_document,
+      &old.name.value,
+      &old.fields,
+      &new.fields,
+      changes,
+    ),
+    (TopDefinition::Enum(old), TopDefinition::Enum(new)) => diff_enum(old, new, changes),
+    (TopDefinition::Senum(old), TopDefinition::Senum(new)) => diff_senum(old, new, changes),
+    (TopDefinition::Service(old), TopDefinition::Service(new)) => {
+      diff_service(old_document, new_document, old, new, changes)
+    }
+    (TopDefinition::Typedef(old), TopDefinition::Typedef(new)) => {
+      if old.field_type.resolve(old_document) != new.field_type.resolve(new_document) {
+        changes.push(SchemaChange::new(
+          SchemaChangeKind::TypedefChanged,
+          old.name.value.clone(),
+          true,
+        ));
+      }
+    }
+    // 常量不会出现在传输的数据中，取值变化不影响兼容性
+    (TopDefinition::Const(_), TopDefinition::Const(_)) => {}
+    _ => changes.push(SchemaChange::new(
+      SchemaChangeKind::DefinitionKindChanged,
+      old.name().unwrap().to_string(),
+      true,
+    )),
+  }
+}
+
+fn is_required(field: &FieldDefinition) -> bool {
+  matches!(field.requiredness, Some(Requiredness::Required))
+}
+
+/// 字段按照 id 对应，id 相同但名称不同视为重命名，对二进制协议是兼容的
+fn diff_fields(
+  old_document: &ThriftDocument,
+  new_document: &ThriftDocument,
+  name: &str,
+  old_fields: &[FieldDefinition],
+  new_fields: &[FieldDefinition],
+  changes: &mut Vec<SchemaChange>,
+) {
+  for old_field in old_fields {
+    let path = format!("{}.{}", name, old_field.name.value);
+    let new_field = new_fields
+      .iter()
+      .find(|field| field.field_id.value == old_field.field_id.value);
+
+    let Some(new_field) = new_field else {
+      if new_fields
+        .iter()
+        .any(|field| field.name.value == old_field.name.value)
+      {
+        changes.push(SchemaChange::new(
+          SchemaChangeKind::FieldIdChanged,
+          path,
+          true,
+        ));
+      } else {
+        changes.push(SchemaChange::new(
+          SchemaChangeKind::FieldRemoved,
+          path,
+          is_required(old_field),
+        ));
+      }
+      continue;
+    };
+
+    if old_field.field_type.resolve(old_document) != new_field.field_type.resolve(new_document) {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::FieldTypeChanged,
+        path.clone(),
+        true,
+      ));
+    }
+
+    if old_field.requiredness != new_field.requiredness {
+      // 只要涉及 required 的变化，新旧版本之间就可能出现读取失败
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::FieldRequirednessChanged,
+        path,
+        is_required(old_field) || is_required(new_field),
+      ));
+    }
+  }
+
+  for new_field in new_fields {
+    let id_exists = old_fields
+      .iter()
+      .any(|field| field.field_id.value == new_field.field_id.value);
+    let renumbered = old_fields
+      .iter()
+      .any(|field| field.name.value == new_field.name.value);
+
+    if !id_exists && !renumbered {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::FieldAdded,
+        format!("{}.{}", name, new_field.name.value),
+        is_required(new_field),
+      ));
+    }
+  }
+}
+
+fn diff_enum(old: &EnumDefinition, new: &EnumDefinition, changes: &mut Vec<SchemaChange>) {
+  let old_values = old.member_values();
+  let new_values = new.member_values();
+
+  for (index, old_member) in old.members.iter().enumerate() {
+    let path = format!("{}.{}", old.name.value, old_member.name.value);
+
+    match new
+      .members
+      .iter()
+      .position(|member| member.name.value == old_member.name.value)
+    {
+      Some(new_index) => {
+        if old_values[index] != new_values[new_index] {
+          changes.push(SchemaChange::new(
+            SchemaChangeKind::EnumValueChanged,
+            path,
+            true,
+          ));
+        }
+      }
+      None => changes.push(SchemaChange::new(
+        SchemaChangeKind::EnumMemberRemoved,
+        path,
+        true,
+      )),
+    }
+  }
+
+  for new_member in &new.members {
+    if !old
+      .members
+      .iter()
+      .any(|member| member.name.value == new_member.name.value)
+    {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::EnumMemberAdded,
+        format!("{}.{}", new.name.value, new_member.name.value),
+        false,
+      ));
+    }
+  }
+}
+
+fn diff_senum(old: &SenumDefinition, new: &SenumDefinition, changes: &mut Vec<SchemaChange>) {
+  for old_value in &old.values {
+    if !new.values.contains(old_value) {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::EnumMemberRemoved,
+        format!("{}.{}", old.name.value, old_value.value),
+        true,
+      ));
+    }
+  }
+
+  for new_value in &new.values {
+    if !old.values.contains(new_value) {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::EnumMemberAdded,
+        format!("{}.{}", new.name.value, new_value.value),
+        false,
+      ));
+    }
+  }
+}
+
+fn function_signature(
+  document: &ThriftDocument,
+  function_definition: &FunctionDefinition,
+) -> Vec<String> {
+  let mut signature = vec![function_definition
+    .return_type
+    .resolve(document)
+    .to_thrift()];
+
+  for field in &function_definition.fields {
+    signature.push(format!(
+      "{}:{}",
+      field.field_id.value,
+      field.field_type.resolve(document).to_thrift()
+    ));
+  }
+
+  signature
+}
+
+fn diff_service(
+  old_document: &ThriftDocument,
+  new_document: &ThriftDocument,
+  old: &ServiceDefinition,
+  new: &ServiceDefinition,
+  changes: &mut Vec<SchemaChange>,
+) {
+  for old_function in &old.functions {
+    let path = format!("{}.{}", old.name.value, old_function.name.value);
+
+    match new
+      .functions
+      .iter()
+      .find(|function| function.name.value == old_function.name.value)
+    {
+      Some(new_function) => {
+        if function_signature(old_document, old_function)
+          != function_signature(new_document, new_function)
+        {
+          changes.push(SchemaChange::new(
+            SchemaChangeKind::FunctionSignatureChanged,
+            path,
+            true,
+          ));
+        }
+      }
+      None => changes.push(SchemaChange::new(
+        SchemaChangeKind::FunctionRemoved,
+        path,
+        true,
+      )),
+    }
+  }
+
+  for new_function in &new.functions {
+    if !old
+      .functions
+      .iter()
+      .any(|function| function.name.value == new_function.name.value)
+    {
+      changes.push(SchemaChange::new(
+        SchemaChangeKind::FunctionAdded,
+        format!("{}.{}", new.name.value, new_function.name.value),
+        false,
+      ));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse::Parser;
+
+  fn diff_code(old: &str, new: &str) -> Vec<SchemaChange> {
+    let old = Parser::new(old).parse().unwrap();
+    let new = Parser::new(new).parse().unwrap();
+    diff(&old, &new)
+  }
+
+  #[test]
+  fn test_field_type_changed() {
+    let changes = diff_code(
+      "struct GetDataReq {\n 1: string parameters\n 2: i32 status\n}",
+      "struct GetDataReq {\n 1: string parameters\n 2: string status\n}",
+    );
+
+    assert_eq!(
+      changes,
+      [SchemaChange::new(
+        SchemaChangeKind::FieldTypeChanged,
+        "GetDataReq.status".to_string(),
+        true,
+      )]
+    );
+  }
+
+  #[test]
+  fn test_compatible_changes() {
+    let changes = diff_code(
+      "typedef i32 Status\nstruct A {\n 1: i32 status\n}\nenum E {\n X = 1\n}",
+      "typedef i32 Status\nstruct A {\n 1: Status status\n 2: optional string msg\n}\nenum E {\n X = 1\n Y = 2\n}",
+    );
+
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|change| !change.breaking));
+    assert_eq!(changes[0].kind, SchemaChangeKind::FieldAdded);
+    assert_eq!(changes[1].kind, SchemaChangeKind::EnumMemberAdded);
+  }
+
+  #[test]
+  fn test_breaking_changes() {
+    let changes = diff_code(
+      "struct A {\n 1: i32 a\n 2: i32 b\n}\nenum E {\n X = 1\n Y = 2\n}\nservice S {\n A Get(1: A req)\n A Del(1: A req)\n}",
+      "struct A {\n 3: i32 a\n 2: i32 b\n 4: required i32 c\n}\nenum E {\n X = 2\n}\nservice S {\n A Get(1: E req)\n}",
+    );
+
+    let kinds = changes
+      .iter()
+      .map(|change| (change.kind, change.path.as_str(), change.breaking))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      kinds,
+      [
+        (SchemaChangeKind::FieldIdChanged, "A.a", true),
+        (SchemaChangeKind::FieldAdded, "A.c", true),
+        (SchemaChangeKind::EnumValueChanged, "E.X", true),
+        (SchemaChangeKind::EnumMemberRemoved, "E.Y", true),
+        (SchemaChangeKind::FunctionSignatureChanged, "S.Get", true),
+        (SchemaChangeKind::FunctionRemoved, "S.Del", true),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_senum_changes() {
+    let changes = diff_code("senum S { \"a\" \"b\" }", "senum S { \"b\" \"c\" }");
+
+    assert_eq!(
+      changes,
+      [
+        SchemaChange::new(SchemaChangeKind::EnumMemberRemoved, "S.a".to_string(), true),
+        SchemaChange::new(SchemaChangeKind::EnumMemberAdded, "S.c".to_string(), false),
+      ]
+    );
+  }
+}
@@ -1,20 +1,187 @@
-use std::path::Path;
+use std::{
+  collections::{HashMap, HashSet},
+  fmt,
+  path::Path,
+  sync::Arc,
+  time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
   parse::{
-    Comment, EnumDefinition, FunctionDefinition, IncludeDefinition, Requiredness,
-    ServiceDefinition, StructDefinition, ThriftDocument, ThriftType,
+    Comment, CommentLine, ConstDefinition, ConstValue, EnumDefinition, FieldDefinition,
+    FunctionDefinition, Identifier, IncludeDefinition, Requiredness, SenumDefinition,
+    ServiceDefinition, StructDefinition, ThriftDocument, ThriftType, TopDefinition,
+    TypedefDefinition, UnionDefinition,
   },
   visit::Visit,
 };
 
-const INDENT: &'static str = "  ";
+const INDENT: &str = "  ";
 
 #[derive(Debug, Clone)]
 pub struct GenerateOptions {
-  i64_as_string: bool,
-  options_type_code: Option<String>,
-  fetch_declaration_code: Option<String>,
+  pub i64_as_string: bool,
+  pub options_type_code: Option<String>,
+  pub fetch_declaration_code: Option<String>,
+  /// 可选字段额外生成 `| null`，适配总是返回 null 而不是省略字段的服务端
+  pub optional_null: bool,
+  /// 重新导出 include 的模块，并为引用到的 include 类型生成 `export import A = a.A;` 别名，
+  /// 使用方可以直接通过当前模块访问被引用的类型，与当前文件中的定义重名的类型不生成别名
+  pub reexport_includes: bool,
+  /// 为每个 enum 生成 `matchXxx` 辅助函数，借助 `never` 检查保证所有成员都被处理
+  pub emit_enum_match: bool,
+  /// 为作为函数参数的请求 struct 额外生成所有字段都可选的版本，适用于更新类接口
+  pub emit_partial_variants: bool,
+  /// 可选版本的命名规则，`{name}` 会被替换为原 struct 名称
+  pub partial_variant_name: String,
+  /// 作为格式提示的注解，如指定 `format` 后 `(format = "date-time")` 会生成 `@format date-time`，默认不生成
+  pub format_annotation_keys: Vec<String>,
+  /// 为每个 enum 生成 `isXxx` 函数，用于校验不可信的输入
+  pub emit_enum_validators: bool,
+  /// JSDoc 注释的最大列宽，超出的行会在空格处换行，原有的换行会被保留
+  pub doc_wrap: Option<usize>,
+  /// 除请求函数外，额外为 service 生成 interface 或 abstract class，便于实现服务端
+  pub service_output: Option<ServiceOutput>,
+  /// TS 中字段的命名风格，只影响生成的类型，传输时仍然使用原始名称
+  ///
+  /// 使用 `CamelCase` 时总是会生成编解码函数，请求函数通过它们转换参数和返回值
+  pub field_naming: FieldNaming,
+  /// 为每个 struct 生成 `encodeXxx`、`decodeXxx` 函数，在 TS 字段名和传输的字段名之间转换
+  pub emit_json_codecs: bool,
+  /// 约束 map key 格式的注解，如 `(key.pattern = "user:*")` 会生成 ``Record<`user:${string}`, V>``，`*` 匹配任意字符串
+  pub key_pattern_annotation: Option<String>,
+  /// 文件中只有一个定义时，额外生成 `export default`，service 生成的是一组函数，不计入定义也不会被默认导出
+  pub default_export: bool,
+  /// 生成代码的最大列宽，超出时将联合类型拆分为每行一个成员，过长的泛型参数（如 `Record<K, V>`）拆分为每行一个参数
+  pub max_line_length: Option<usize>,
+  /// 额外生成运行时校验库使用的 schema，如 zod 的 `XxxSchema`
+  pub validation_target: Option<ValidationTarget>,
+  /// union 上指定判别字段的注解，如 `(ts.discriminator = "type")` 会生成可辨识联合类型，
+  /// 每个成员带有 `type: '字段名'`
+  pub discriminator_annotation: Option<String>,
+  /// 在文件头部生成 `// @generated`，代码审查工具会据此折叠生成的文件
+  pub generated_marker: bool,
+  /// 引入其他模块时在相对路径后追加的扩展名，如 Node.js ESM 要求的 `.js`
+  pub import_extension: Option<String>,
+  /// 引入其他模块使用的语法
+  pub module_kind: ModuleKind,
+  /// 常量的取值后追加 `as const`，并省略会放宽字面量类型的类型声明，引用其他常量或 enum 成员的取值除外
+  pub const_as_const: bool,
+  /// list 和 map 常量使用只读类型，与 `const_as_const` 同时开启时 list 生成 `readonly [...]` 元组类型
+  pub const_readonly: bool,
+  /// 为每个 service 生成 `XxxMethods` 类型，记录每个函数的参数和返回值类型，便于编写通用的调用代码
+  pub emit_method_map: bool,
+  /// enum 生成的形式
+  pub enum_style: EnumStyle,
+  /// `EnumStyle::ConstObject` 时对象名称的后缀，如 `Enum` 会生成 `ItemTypeEnum`，类型名称保持不变
+  pub enum_object_suffix: Option<String>,
+  /// 只有 namespace 和 include 的文件不生成输出，开启 `reexport_includes` 时仍然会生成用于重新导出的文件
+  pub skip_empty_output: bool,
+  /// 为每个 struct 生成 `isXxx` 类型守卫，只浅层检查必选字段是否存在以及基础类型是否匹配
+  pub emit_struct_guards: bool,
+  /// 追加到文件头部的注释，支持 `{file}`（相对 src_dir 的源文件路径）、`{date}`（UTC 日期）、`{version}`（thrift_parser 版本）变量
+  /// 使用 `{date}` 时生成的内容每天都不同，内容未变化时跳过写入文件的优化会失效
+  pub header: Option<String>,
+  /// enum 成员和 union 成员在生成代码中的顺序
+  pub member_order: MemberOrder,
+  /// 模拟继承的 struct 注解，如 `(extends = "BaseStruct")` 会生成 `interface Foo extends BaseStruct`
+  pub struct_extends_annotation: Option<String>,
+  /// 写入之前对每个文件生成的代码做最后的处理，如统一换行符、替换特定的注释
+  pub post_process: Option<PostProcess>,
+  /// 将带有相同注解值的常量合并生成一个 enum，如 `(enum_group = "Color")`，常量本身仍然会生成
+  pub const_group_annotation: Option<String>,
+  /// 允许使用的注解作用域，如 `api` 允许 `api.get`、`api.query`，其他作用域的注解会导致编译失败，`None` 时不检查
+  pub known_annotation_scopes: Option<Vec<String>>,
+  /// 为每个 service 生成 `XxxEvents` 类型，只记录 oneway 函数的参数类型，oneway 函数没有响应，更接近事件通知
+  pub emit_events_map: bool,
+}
+
+/// 生成代码的后处理函数，配置会在多个线程间复制，所以使用 `Arc` 保存
+#[derive(Clone)]
+pub struct PostProcess(pub Arc<dyn Fn(String) -> String + Send + Sync>);
+
+impl PostProcess {
+  pub fn new(f: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+    Self(Arc::new(f))
+  }
+}
+
+impl fmt::Debug for PostProcess {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("PostProcess")
+  }
+}
+
+/// 引入其他模块的语法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModuleKind {
+  /// `import * as a from './a';`
+  Esm,
+  /// `import a = require('./a');`
+  CommonJs,
+}
+
+/// 常见的运行环境，用于生成预设的配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+  /// `"type": "module"` 的 Node.js 项目，相对路径需要带上 `.js`
+  NodeEsm,
+  /// CommonJS 模块的 Node.js 项目
+  NodeCjs,
+  /// 经过打包工具处理的浏览器项目
+  Browser,
+  /// Deno 直接执行 TS 文件，相对路径需要带上 `.ts`
+  DenoLand,
+}
+
+/// enum 生成的形式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnumStyle {
+  /// `export enum ItemType { ... }`
+  Enum,
+  /// `export const ItemType = { ... } as const;` 以及对应的联合类型，不依赖 TS 的 enum 语法
+  ConstObject,
+  /// `export const enum ItemType { ... }`，TS 在使用处直接内联取值，不生成运行时对象
+  ///
+  /// 没有反向映射（`ItemType[0]`），开启 `isolatedModules` 时无法跨文件使用
+  ConstEnum,
+  /// `export enum ItemType { Unknown = 'Unknown', ... }`，适用于按照成员名称传输 enum 的接口
+  StringEnum,
+}
+
+/// enum 成员和 union 成员的顺序
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemberOrder {
+  /// 与 IDL 中的顺序一致
+  Source,
+  /// 按照名称排序，调整 IDL 中的顺序不会影响生成的代码
+  Alphabetical,
+}
+
+/// 字段名称的命名风格
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldNaming {
+  /// 与 IDL 中的名称保持一致
+  Original,
+  /// `is_ok` 转换为 `isOk`
+  CamelCase,
+}
+
+/// 运行时校验库
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValidationTarget {
+  /// https://zod.dev
+  Zod,
+}
+
+/// service 额外生成的类型声明
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceOutput {
+  /// `export interface Xxx`
+  Interface,
+  /// `export abstract class XxxBase`
+  AbstractClass,
+  Both,
 }
 
 impl Default for GenerateOptions {
@@ -23,43 +190,492 @@ impl Default for GenerateOptions {
       i64_as_string: false,
       options_type_code: Some("any".to_string()),
       fetch_declaration_code: None,
+      optional_null: false,
+      reexport_includes: false,
+      emit_enum_match: false,
+      emit_partial_variants: false,
+      partial_variant_name: "Partial{name}".to_string(),
+      format_annotation_keys: vec![],
+      emit_enum_validators: false,
+      doc_wrap: None,
+      service_output: None,
+      field_naming: FieldNaming::Original,
+      emit_json_codecs: false,
+      key_pattern_annotation: None,
+      default_export: false,
+      max_line_length: None,
+      validation_target: None,
+      discriminator_annotation: Some("ts.discriminator".to_string()),
+      generated_marker: false,
+      import_extension: None,
+      module_kind: ModuleKind::Esm,
+      const_as_const: false,
+      const_readonly: false,
+      emit_method_map: false,
+      enum_style: EnumStyle::Enum,
+      enum_object_suffix: None,
+      skip_empty_output: false,
+      emit_struct_guards: false,
+      header: None,
+      member_order: MemberOrder::Source,
+      struct_extends_annotation: None,
+      post_process: None,
+      const_group_annotation: None,
+      known_annotation_scopes: None,
+      emit_events_map: false,
+    }
+  }
+}
+
+/// 分层配置中的覆盖项，`None` 表示沿用基础配置
+#[derive(Debug, Clone, Default)]
+pub struct PartialGenerateOptions {
+  pub i64_as_string: Option<bool>,
+  pub options_type_code: Option<String>,
+  pub fetch_declaration_code: Option<String>,
+  pub optional_null: Option<bool>,
+  pub reexport_includes: Option<bool>,
+  pub emit_enum_match: Option<bool>,
+  pub emit_partial_variants: Option<bool>,
+  pub partial_variant_name: Option<String>,
+  pub format_annotation_keys: Option<Vec<String>>,
+  pub emit_enum_validators: Option<bool>,
+  pub doc_wrap: Option<usize>,
+  pub service_output: Option<ServiceOutput>,
+  pub field_naming: Option<FieldNaming>,
+  pub emit_json_codecs: Option<bool>,
+  pub key_pattern_annotation: Option<String>,
+  pub default_export: Option<bool>,
+  pub max_line_length: Option<usize>,
+  pub validation_target: Option<ValidationTarget>,
+  pub discriminator_annotation: Option<String>,
+  pub generated_marker: Option<bool>,
+  pub import_extension: Option<String>,
+  pub module_kind: Option<ModuleKind>,
+  pub const_as_const: Option<bool>,
+  pub const_readonly: Option<bool>,
+  pub emit_method_map: Option<bool>,
+  pub enum_style: Option<EnumStyle>,
+  pub enum_object_suffix: Option<String>,
+  pub skip_empty_output: Option<bool>,
+  pub emit_struct_guards: Option<bool>,
+  pub header: Option<String>,
+  pub member_order: Option<MemberOrder>,
+  pub struct_extends_annotation: Option<String>,
+  pub post_process: Option<PostProcess>,
+  pub const_group_annotation: Option<String>,
+  pub known_annotation_scopes: Option<Vec<String>>,
+  pub emit_events_map: Option<bool>,
+}
+
+impl GenerateOptions {
+  /// 针对运行环境的预设配置，其余配置项保持默认值
+  pub fn for_target(target: Target) -> GenerateOptions {
+    let (import_extension, module_kind) = match target {
+      Target::NodeEsm => (Some(".js"), ModuleKind::Esm),
+      Target::NodeCjs => (None, ModuleKind::CommonJs),
+      Target::Browser => (None, ModuleKind::Esm),
+      Target::DenoLand => (Some(".ts"), ModuleKind::Esm),
+    };
+
+    GenerateOptions {
+      import_extension: import_extension.map(|v| v.to_string()),
+      module_kind,
+      ..Default::default()
+    }
+  }
+
+  /// 合并配置，`override_options` 中为 `Some` 的项优先
+  pub fn merge(base: GenerateOptions, override_options: PartialGenerateOptions) -> GenerateOptions {
+    GenerateOptions {
+      i64_as_string: override_options.i64_as_string.unwrap_or(base.i64_as_string),
+      options_type_code: override_options
+        .options_type_code
+        .or(base.options_type_code),
+      fetch_declaration_code: override_options
+        .fetch_declaration_code
+        .or(base.fetch_declaration_code),
+      optional_null: override_options.optional_null.unwrap_or(base.optional_null),
+      reexport_includes: override_options
+        .reexport_includes
+        .unwrap_or(base.reexport_includes),
+      emit_enum_match: override_options
+        .emit_enum_match
+        .unwrap_or(base.emit_enum_match),
+      emit_partial_variants: override_options
+        .emit_partial_variants
+        .unwrap_or(base.emit_partial_variants),
+      partial_variant_name: override_options
+        .partial_variant_name
+        .unwrap_or(base.partial_variant_name),
+      format_annotation_keys: override_options
+        .format_annotation_keys
+        .unwrap_or(base.format_annotation_keys),
+      emit_enum_validators: override_options
+        .emit_enum_validators
+        .unwrap_or(base.emit_enum_validators),
+      doc_wrap: override_options.doc_wrap.or(base.doc_wrap),
+      service_output: override_options.service_output.or(base.service_output),
+      field_naming: override_options.field_naming.unwrap_or(base.field_naming),
+      emit_json_codecs: override_options
+        .emit_json_codecs
+        .unwrap_or(base.emit_json_codecs),
+      key_pattern_annotation: override_options
+        .key_pattern_annotation
+        .or(base.key_pattern_annotation),
+      default_export: override_options
+        .default_export
+        .unwrap_or(base.default_export),
+      max_line_length: override_options.max_line_length.or(base.max_line_length),
+      validation_target: override_options
+        .validation_target
+        .or(base.validation_target),
+      discriminator_annotation: override_options
+        .discriminator_annotation
+        .or(base.discriminator_annotation),
+      generated_marker: override_options
+        .generated_marker
+        .unwrap_or(base.generated_marker),
+      import_extension: override_options.import_extension.or(base.import_extension),
+      module_kind: override_options.module_kind.unwrap_or(base.module_kind),
+      const_as_const: override_options
+        .const_as_const
+        .unwrap_or(base.const_as_const),
+      const_readonly: override_options
+        .const_readonly
+        .unwrap_or(base.const_readonly),
+      emit_method_map: override_options
+        .emit_method_map
+        .unwrap_or(base.emit_method_map),
+      enum_style: override_options.enum_style.unwrap_or(base.enum_style),
+      enum_object_suffix: override_options
+        .enum_object_suffix
+        .or(base.enum_object_suffix),
+      skip_empty_output: override_options
+        .skip_empty_output
+        .unwrap_or(base.skip_empty_output),
+      emit_struct_guards: override_options
+        .emit_struct_guards
+        .unwrap_or(base.emit_struct_guards),
+      header: override_options.header.or(base.header),
+      member_order: override_options.member_order.unwrap_or(base.member_order),
+      struct_extends_annotation: override_options
+        .struct_extends_annotation
+        .or(base.struct_extends_annotation),
+      post_process: override_options.post_process.or(base.post_process),
+      const_group_annotation: override_options
+        .const_group_annotation
+        .or(base.const_group_annotation),
+      known_annotation_scopes: override_options
+        .known_annotation_scopes
+        .or(base.known_annotation_scopes),
+      emit_events_map: override_options
+        .emit_events_map
+        .unwrap_or(base.emit_events_map),
     }
   }
 }
 
 pub struct Generator<'a> {
   document: &'a mut ThriftDocument,
+  include_aliases: HashMap<String, String>,
+  include_paths: HashMap<String, String>,
+  source_file: Option<String>,
 }
 
 impl<'a> Generator<'a> {
   pub fn new(document: &'a mut ThriftDocument) -> Self {
-    Self { document }
+    Self {
+      document,
+      include_aliases: HashMap::new(),
+      include_paths: HashMap::new(),
+      source_file: None,
+    }
+  }
+
+  /// 指定源文件的路径，用于替换 `header` 中的 `{file}`
+  pub fn source_file(mut self, source_file: &str) -> Self {
+    self.source_file = Some(source_file.to_string());
+    self
+  }
+
+  /// 指定 include 的导入路径，key 为 include 的路径，未指定时使用 `./{文件名}`
+  pub fn include_paths(mut self, include_paths: HashMap<String, String>) -> Self {
+    self.include_paths = include_paths;
+    self
+  }
+
+  /// 指定 include 的导入名称，key 为 include 的路径，未指定时使用文件名
+  pub fn include_aliases(mut self, include_aliases: HashMap<String, String>) -> Self {
+    self.include_aliases = include_aliases;
+    self
+  }
+
+  /// thrift 类型对应的 TS 类型，没有文档上下文，所以不会识别 enum 作为 key 的 map
+  pub fn map_type(field_type: &ThriftType, options: &GenerateOptions) -> String {
+    Visitor::new(options.clone()).format_thrift_type(field_type)
   }
 
   pub fn build(&mut self, options: GenerateOptions) -> String {
     let mut visitor = Visitor::new(options);
-    visitor.visit_document(&mut self.document);
-    visitor.code
+    visitor.code = visitor.format_header(self.source_file.as_deref().unwrap_or_default());
+    visitor.include_aliases = self.include_aliases.clone();
+    visitor.include_paths = self.include_paths.clone();
+
+    if visitor.options.validation_target == Some(ValidationTarget::Zod) {
+      visitor.code.push_str("import { z } from 'zod';\n");
+    }
+
+    visitor.collect_names(self.document);
+    visitor.visit_document(self.document);
+
+    if visitor.options.default_export {
+      let names = self
+        .document
+        .body
+        .iter()
+        .filter(|definition| !matches!(definition, TopDefinition::Service(_)))
+        .filter_map(|definition| definition.name())
+        .collect::<Vec<_>>();
+
+      if let [name] = names[..] {
+        visitor
+          .code
+          .push_str(format!("\nexport default {};\n", name).as_str());
+      }
+    }
+
+    match &visitor.options.post_process {
+      Some(post_process) => (post_process.0)(visitor.code),
+      None => visitor.code,
+    }
+  }
+}
+
+impl FieldDefinition {
+  /// 单独生成一个字段的 TS 代码，可用于编辑器悬浮提示等场景
+  pub fn to_ts(&self, options: &GenerateOptions) -> String {
+    Visitor::new(options.clone())
+      .format_field(self, "")
+      .trim_end()
+      .to_string()
+  }
+
+  /// 字段在 TS 中的签名，不包含注释，如 `kvs?: Record<a.A, string>`
+  ///
+  /// `document` 为字段所在的文档，用于判断引用的类型是否为 enum 等
+  pub fn ts_signature(&self, options: &GenerateOptions, document: &ThriftDocument) -> String {
+    let mut visitor = Visitor::new(options.clone());
+    visitor.collect_names(document);
+
+    let (name, field_type) = visitor.field_signature(self, false);
+    format!("{}: {}", name, field_type)
+  }
+}
+
+/// 字段在生成代码中的名称，`api.query`、`api.body` 注解会覆盖原始名称
+pub(crate) fn field_name(field: &FieldDefinition) -> String {
+  if let Some(annotations) = &field.annotations {
+    for annotation in &annotations.annotations {
+      match annotation.name.value.as_str() {
+        "api.query" | "api.body" => {
+          return annotation.value.value.clone();
+        }
+        _ => {}
+      }
+    }
   }
+
+  field.name.value.clone()
+}
+
+/// 字段上的格式提示，取第一个匹配 `format_annotation_keys` 的注解
+pub(crate) fn field_format(field: &FieldDefinition, options: &GenerateOptions) -> Option<String> {
+  field.annotations.as_ref().and_then(|annotations| {
+    annotations
+      .annotations
+      .iter()
+      .find(|annotation| {
+        options
+          .format_annotation_keys
+          .contains(&annotation.name.value)
+      })
+      .map(|annotation| annotation.value.value.clone())
+  })
+}
+
+fn to_camel_case(name: &str) -> String {
+  let prefix_len = name.len() - name.trim_start_matches('_').len();
+  let mut code = name[..prefix_len].to_string();
+  let mut upper = false;
+
+  for c in name[prefix_len..].chars() {
+    if c == '_' {
+      upper = true;
+    } else if upper {
+      code.extend(c.to_uppercase());
+      upper = false;
+    } else {
+      code.push(c);
+    }
+  }
+
+  code
+}
+
+/// 当前的 UTC 日期，格式为 `YYYY-MM-DD`
+fn today() -> String {
+  let days = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs() / 86400)
+    .unwrap_or_default() as i64;
+
+  // 参考 http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+
+  format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 访问对象属性，名称不是合法的标识符时使用 `obj['a-b']` 的形式
+fn property_access(object: &str, name: &str) -> String {
+  let is_identifier = name
+    .chars()
+    .enumerate()
+    .all(|(i, c)| c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+
+  if is_identifier {
+    format!("{}.{}", object, name)
+  } else {
+    format!("{}['{}']", object, name)
+  }
+}
+
+/// 函数名首字母小写，作为方法映射类型中的 key
+fn method_key(function_definition: &FunctionDefinition) -> String {
+  let mut chars = function_definition.name.value.chars();
+
+  match chars.next() {
+    Some(first) => first.to_lowercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+fn property_key(name: &str) -> String {
+  match property_access("", name).strip_prefix('.') {
+    Some(name) => name.to_string(),
+    None => format!("'{}'", name),
+  }
+}
+
+/// 按照最外层的 `|` 拆分联合类型，泛型参数和模板字符串中的 `|` 不会被拆分
+fn split_union(ts_type: &str) -> Vec<&str> {
+  split_top_level(ts_type, '|')
+}
+
+/// 按照最外层的 `separator` 拆分类型，括号和模板字符串中的分隔符不会被拆分
+fn split_top_level(ts_type: &str, separator: char) -> Vec<&str> {
+  let mut members = vec![];
+  let mut depth = 0;
+  let mut in_template = false;
+  let mut start = 0;
+
+  for (index, c) in ts_type.char_indices() {
+    match c {
+      '`' => in_template = !in_template,
+      '<' | '(' | '[' | '{' if !in_template => depth += 1,
+      '>' | ')' | ']' | '}' if !in_template => depth -= 1,
+      c if c == separator && !in_template && depth == 0 => {
+        members.push(ts_type[start..index].trim());
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+
+  members.push(ts_type[start..].trim());
+  members
+}
+
+/// `column` 开始的类型超出 `max_line_length` 时，将最外层的泛型参数拆分为每行一个，
+/// 每个参数仍然过长时继续向内拆分，`indent` 为类型所在行的缩进
+fn wrap_generic(ts_type: &str, indent: &str, column: usize, max_line_length: usize) -> String {
+  if column + ts_type.chars().count() <= max_line_length {
+    return ts_type.to_string();
+  }
+
+  let Some((name, args)) = ts_type.strip_suffix('>').and_then(|v| v.split_once('<')) else {
+    return ts_type.to_string();
+  };
+
+  // `A<B> | C<D>` 这样第一个 `<` 与最后的 `>` 不匹配的类型不是单个泛型，整体保留
+  let mut depth = 0;
+  for c in args.chars() {
+    match c {
+      '<' => depth += 1,
+      '>' if depth == 0 => return ts_type.to_string(),
+      '>' => depth -= 1,
+      _ => {}
+    }
+  }
+
+  let arg_indent = format!("{}{}", indent, INDENT);
+  let args = split_top_level(args, ',')
+    .into_iter()
+    .map(|arg| {
+      format!(
+        "{}{}",
+        arg_indent,
+        wrap_generic(
+          arg,
+          &arg_indent,
+          arg_indent.chars().count() + 1,
+          max_line_length
+        )
+      )
+    })
+    .collect::<Vec<_>>();
+
+  format!("{}<\n{}\n{}>", name, args.join(",\n"), indent)
 }
 
 struct Visitor {
   pub code: String,
   options: GenerateOptions,
+  include_aliases: HashMap<String, String>,
+  include_paths: HashMap<String, String>,
+  /// 作为函数参数使用的 struct 名称
+  request_structs: HashSet<String>,
+  /// 当前文件中定义的 enum 名称
+  enum_names: HashSet<String>,
+  /// 当前文件中定义的 struct 和 union 名称
+  struct_names: HashSet<String>,
+  /// 按照 `const_group_annotation` 分组的常量名称及取值，保持源码中的顺序
+  const_groups: Vec<(String, Vec<(String, String)>)>,
+  /// 引用的 include 类型，如 `a.A`，保持源码中的顺序，与当前文件中的定义重名的不包含在内
+  qualified_refs: Vec<String>,
   service_visited: bool,
 }
 
 impl Visitor {
   pub fn new(options: GenerateOptions) -> Self {
     Self {
-      code: String::from(
-        r#"/* eslint-disable */
-// @ts-nocheck
-// This file is auto generated by thrift_parser.
-
-"#,
-      ),
+      code: String::new(),
       options,
+      include_aliases: HashMap::new(),
+      include_paths: HashMap::new(),
+      request_structs: HashSet::new(),
+      enum_names: HashSet::new(),
+      struct_names: HashSet::new(),
+      const_groups: vec![],
+      qualified_refs: vec![],
       service_visited: false,
     }
   }
@@ -79,12 +695,19 @@ impl Visitor {
 
     // 如果只有一行注释，返回 /** {} */ 这样的格式
     if is_single_comment {
-      code.push_str(format!(" {} */\n", comments[0].line_value()).as_str());
-      return code;
-    } else {
-      code.push_str("\n");
+      let line = comments[0].line_value();
+      let fits = self.options.doc_wrap.map_or(true, |width| {
+        indent.chars().count() + "/**  */".len() + line.chars().count() <= width
+      });
+
+      if fits {
+        code.push_str(format!(" {} */\n", line).as_str());
+        return code;
+      }
     }
 
+    code.push('\n');
+
     for (index, comment) in comments.iter().enumerate() {
       if index > 0 {
         code.push_str(indent);
@@ -96,285 +719,3209 @@ impl Visitor {
 
       match comment {
         Comment::Line(line) => {
-          code.push_str(&line.value);
+          code.push_str(&self.wrap_comment_line(&line.value, indent));
         }
         Comment::Block(block) => {
-          for line in &block.value {
-            code.push_str(indent);
-            code.push_str(" * ");
-            code.push_str(&line);
-          }
+          let lines = block
+            .value
+            .iter()
+            .map(|line| self.wrap_comment_line(line, indent))
+            .collect::<Vec<_>>();
+
+          code.push_str(&lines.join(&format!("\n{} * ", indent)));
         }
       }
 
-      code.push_str("\n");
+      code.push('\n');
     }
 
-    code.push_str(&indent);
+    code.push_str(indent);
     code.push_str(" */\n");
 
     code
   }
 
-  fn format_thrift_type(&self, thrift_type: &ThriftType) -> String {
-    match &thrift_type {
-      ThriftType::Void => "void".to_string(),
-      ThriftType::String => "string".to_string(),
-      ThriftType::I16 => "number".to_string(),
-      ThriftType::I32 => "number".to_string(),
-      ThriftType::I64 => {
-        if self.options.i64_as_string {
-          "string".to_string()
-        } else {
-          "number".to_string()
-        }
+  /// 按照 `doc_wrap` 在空格处折行，单个过长的单词不会被拆开
+  fn wrap_comment_line(&self, line: &str, indent: &str) -> String {
+    let Some(width) = self.options.doc_wrap else {
+      return line.to_string();
+    };
+
+    let max_len = width.saturating_sub(indent.chars().count() + " * ".len());
+    let mut lines: Vec<String> = vec![];
+    let mut current = String::new();
+
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+      if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > max_len {
+        lines.push(current);
+        current = String::new();
       }
-      ThriftType::Double => "number".to_string(),
-      ThriftType::Bool => "boolean".to_string(),
-      ThriftType::List(ty) => format!("Array<{}>", self.format_thrift_type(ty)),
-      ThriftType::Map(key_ty, value_ty) => format!(
-        "Record<{}, {}>",
-        self.format_thrift_type(key_ty),
-        self.format_thrift_type(value_ty)
-      ),
-      ThriftType::Identifier(id) => id.value.clone(),
+
+      if !current.is_empty() {
+        current.push(' ');
+      }
+
+      current.push_str(word);
     }
+
+    lines.push(current);
+    lines.join(&format!("\n{} * ", indent))
   }
-}
 
-impl Visit for Visitor {
-  fn visit_include_definition(&mut self, include_definition: &mut IncludeDefinition) {
-    let path = Path::new(&include_definition.path.value)
-      .file_stem()
-      .unwrap()
-      .to_str()
-      .unwrap();
-    let code = format!("import * as {} from './{}';\n", path, path);
+  fn format_header(&self, source_file: &str) -> String {
+    let mut code = String::from(
+      r#"/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+"#,
+    );
 
-    self.code.push_str(&code);
+    if self.options.generated_marker {
+      code.push_str("// @generated\n");
+    }
+
+    if let Some(header) = &self.options.header {
+      let header = header
+        .replace("{file}", source_file)
+        .replace("{date}", &today())
+        .replace("{version}", env!("CARGO_PKG_VERSION"));
+
+      code.push_str(header.trim_end());
+      code.push('\n');
+    }
+
+    code.push('\n');
+    code
   }
 
-  fn visit_struct_definition(&mut self, struct_definition: &mut StructDefinition) {
-    let mut code = "\n".to_string();
+  /// 按照 `member_order` 排列成员
+  fn ordered<T>(&self, mut members: Vec<T>, name: impl Fn(&T) -> String) -> Vec<T> {
+    if self.options.member_order == MemberOrder::Alphabetical {
+      members.sort_by_key(|member| name(member));
+    }
 
-    code.push_str(
-      self
-        .format_comments(&struct_definition.comments, "")
-        .as_str(),
-    );
-    code.push_str(format!("export interface {} {{\n", struct_definition.name.value).as_str());
+    members
+  }
 
-    for field in &struct_definition.fields {
-      code.push_str(&self.format_comments(&field.comments, &INDENT));
-      code.push_str(INDENT);
+  /// 收集文档中的类型名称，生成字段类型和请求 struct 时需要用到
+  fn collect_names(&mut self, document: &ThriftDocument) {
+    self.collect_qualified_refs(document);
+
+    for definition in &document.body {
+      match definition {
+        TopDefinition::Enum(enum_definition) => {
+          self.enum_names.insert(enum_definition.name.value.clone());
+        }
+        TopDefinition::Struct(struct_definition) | TopDefinition::Exception(struct_definition) => {
+          self
+            .struct_names
+            .insert(struct_definition.name.value.clone());
+        }
+        TopDefinition::Union(union_definition) => {
+          self
+            .struct_names
+            .insert(union_definition.name.value.clone());
+        }
+        TopDefinition::Const(const_definition) => {
+          let Some(group) = self.const_group(const_definition) else {
+            continue;
+          };
+
+          // enum 中的同名成员会遮蔽外层的常量，所以直接使用常量的值
+          let member = (
+            const_definition.name.value.clone(),
+            self.format_const_value(&const_definition.value, &const_definition.field_type),
+          );
 
-      if let Some(annotations) = &field.annotations {
-        for annotation in &annotations.annotations {
-          match annotation.name.value.as_str() {
-            "api.query" | "api.body" => {
-              code.push_str(&annotation.value.value);
-              break;
+          match self
+            .const_groups
+            .iter_mut()
+            .find(|(name, _)| *name == group)
+          {
+            Some((_, members)) => members.push(member),
+            None => self.const_groups.push((group, vec![member])),
+          }
+        }
+        TopDefinition::Service(service_definition) => {
+          for function_definition in &service_definition.functions {
+            for field in &function_definition.fields {
+              if let ThriftType::Identifier(id) = &field.field_type {
+                self.request_structs.insert(id.value.clone());
+              }
             }
-            _ => {}
           }
         }
-      } else {
-        code.push_str(&field.name.value);
-      };
-
-      if matches!(field.requiredness, Some(Requiredness::Optional)) {
-        code.push_str("?");
+        _ => {}
       }
+    }
+  }
 
-      code.push_str(": ");
-      code.push_str(&self.format_thrift_type(&field.field_type));
-      code.push_str(";\n");
+  fn collect_qualified_refs(&mut self, document: &ThriftDocument) {
+    let mut types = vec![];
+
+    for definition in &document.body {
+      match definition {
+        TopDefinition::Struct(v) | TopDefinition::Exception(v) => {
+          types.extend(v.fields.iter().map(|field| &field.field_type));
+        }
+        TopDefinition::Union(v) => types.extend(v.fields.iter().map(|field| &field.field_type)),
+        TopDefinition::Typedef(v) => types.push(&v.field_type),
+        TopDefinition::Const(v) => types.push(&v.field_type),
+        TopDefinition::Service(v) => {
+          for function_definition in &v.functions {
+            types.push(&function_definition.return_type);
+            types.extend(
+              function_definition
+                .fields
+                .iter()
+                .chain(&function_definition.throws)
+                .map(|field| &field.field_type),
+            );
+          }
+        }
+        _ => {}
+      }
     }
 
-    code.push_str("}\n");
-    self.code.push_str(&code);
-  }
+    let defined_names = document
+      .body
+      .iter()
+      .filter_map(|definition| definition.name())
+      .collect::<HashSet<_>>();
 
-  fn visit_enum_definition(&mut self, enum_definition: &mut EnumDefinition) {
-    let mut code = "\n".to_string();
+    // 用栈代替递归，先反转保证按源码中的顺序弹出
+    types.reverse();
 
-    code.push_str(self.format_comments(&enum_definition.comments, "").as_str());
-    code.push_str(format!("export enum {} {{\n", enum_definition.name.value).as_str());
+    while let Some(thrift_type) = types.pop() {
+      match thrift_type {
+        ThriftType::List(ty) => types.push(ty),
+        ThriftType::Map(key_ty, value_ty) => {
+          types.push(value_ty);
+          types.push(key_ty);
+        }
+        ThriftType::Identifier(id) => {
+          let Some((_, name)) = id.value.rsplit_once('.') else {
+            continue;
+          };
 
-    for member in &enum_definition.members {
-      code.push_str(&self.format_comments(&member.comments, &INDENT));
-      code.push_str(INDENT);
-      code.push_str(&member.name.value);
+          // 不同 include 中的同名类型只导出第一个
+          let duplicated = self
+            .qualified_refs
+            .iter()
+            .any(|v| v.rsplit_once('.').map(|(_, v)| v) == Some(name));
 
-      if let Some(initializer) = &member.initializer {
-        code.push_str(" = ");
-        code.push_str(&initializer.value);
+          if !defined_names.contains(name) && !duplicated {
+            self.qualified_refs.push(id.value.clone());
+          }
+        }
+        _ => {}
       }
-      code.push_str(",\n");
     }
-
-    code.push_str("}\n");
-    self.code.push_str(&code);
   }
 
-  fn visit_service_definition(&mut self, service_definition: &mut ServiceDefinition) {
-    if !self.service_visited {
-      self.service_visited = true;
+  /// 常量所属的分组，只有整数和字符串常量可以作为 enum 成员
+  fn const_group(&self, const_definition: &ConstDefinition) -> Option<String> {
+    let key = self.options.const_group_annotation.as_ref()?;
 
-      if let Some(fetch_declaration_code) = &self.options.fetch_declaration_code {
-        self.code.push_str("\n");
-        self.code.push_str(fetch_declaration_code.as_str());
-        self.code.push_str("\n");
-      }
+    if !matches!(
+      const_definition.value,
+      ConstValue::Integer(_) | ConstValue::String(_)
+    ) {
+      return None;
+    }
 
-      let options_type = self
-        .options
-        .options_type_code
-        .clone()
-        .unwrap_or("any".to_string());
+    const_definition
+      .annotations
+      .as_ref()?
+      .annotations
+      .iter()
+      .find(|annotation| &annotation.name.value == key)
+      .map(|annotation| annotation.value.value.clone())
+  }
 
-      self
-        .code
-        .push_str(format!("\ntype _Options = {};\n", options_type).as_str());
+  /// 分组的 enum 在最后一个常量之后生成
+  fn format_const_group(&self, group: &str, members: &[(String, String)]) -> String {
+    let mut code = format!("\nexport enum {} {{\n", group);
 
-      self.code.push_str(
-        r#"
-let urlPrefix = '';
-export function setUrlPrefix(prefix: string) {
-  urlPrefix = prefix;
-}
-"#,
-      );
+    for (name, value) in members {
+      code.push_str(format!("{}{} = {},\n", INDENT, name, value).as_str());
     }
 
-    for function_definition in &mut service_definition.functions {
-      self.visit_function_definition(function_definition)
+    code.push_str("}\n");
+    code
+  }
+
+  fn format_field(&self, field: &FieldDefinition, indent: &str) -> String {
+    self.format_field_with_optional(field, indent, false)
+  }
+
+  fn format_field_with_optional(
+    &self,
+    field: &FieldDefinition,
+    indent: &str,
+    force_optional: bool,
+  ) -> String {
+    let mut comments = field.comments.clone();
+
+    if let Some(format) = field_format(field, &self.options) {
+      comments.push(Comment::Line(CommentLine {
+        value: format!("@format {}", format),
+      }));
     }
+
+    let mut code = self.format_comments(&comments, indent);
+    let (name, field_type) = self.field_signature(field, force_optional);
+
+    code.push_str(&self.format_type_line(indent, &format!("{}:", name), &field_type, ";"));
+    code
   }
 
-  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {
-    let mut code = "\n".to_string();
+  /// 字段在 TS 中的名称（可选时带有 `?`）和类型
+  fn field_signature(&self, field: &FieldDefinition, force_optional: bool) -> (String, String) {
+    let mut name = self.ts_field_name(field);
 
-    code.push_str(
-      self
-        .format_comments(&function_definition.comments, "")
-        .as_str(),
-    );
-    code.push_str(format!("export function {}(", function_definition.name.value).as_str());
+    let is_optional = matches!(field.requiredness, Some(Requiredness::Optional));
 
-    let first_arg_name = function_definition
-      .fields
-      .get(0)
-      .map(|field| field.name.value.clone());
+    if is_optional || force_optional {
+      name.push('?');
+    }
 
-    for field in &function_definition.fields {
-      code.push_str(&field.name.value);
-      code.push_str(": ");
-      code.push_str(&self.format_thrift_type(&field.field_type));
-      code.push_str(", ");
+    let mut field_type = self.format_field_type(field);
+
+    if is_optional && self.options.optional_null {
+      field_type.push_str(" | null");
     }
 
-    code.push_str(
-      format!(
-        "options: _Options): Promise<{}> {{\n",
-        self.format_thrift_type(&function_definition.return_type)
+    (name, field_type)
+  }
+
+  /// 生成 `{indent}{prefix} {ts_type}{suffix}` 这样的一行，超出 `max_line_length` 时
+  /// 将最外层的联合类型拆分为每行一个成员，仍然过长的泛型参数（如 `Record<K, V>`）拆分为每行一个参数
+  fn format_type_line(&self, indent: &str, prefix: &str, ts_type: &str, suffix: &str) -> String {
+    let line = format!("{}{} {}{}\n", indent, prefix, ts_type, suffix);
+
+    let Some(max_line_length) = self.options.max_line_length else {
+      return line;
+    };
+
+    if line.trim_end().chars().count() <= max_line_length {
+      return line;
+    }
+
+    let members = split_union(ts_type);
+
+    if members.len() > 1 {
+      let member_indent = format!("{}{}", indent, INDENT);
+      let mut code = format!("{}{}\n", indent, prefix);
+
+      for member in members {
+        let column = member_indent.chars().count() + 2;
+        code.push_str(
+          format!(
+            "{}| {}\n",
+            member_indent,
+            wrap_generic(member, &member_indent, column, max_line_length)
+          )
+          .as_str(),
+        );
+      }
+
+      code.pop();
+      code.push_str(suffix);
+      code.push('\n');
+      code
+    } else {
+      let column = indent.chars().count() + prefix.chars().count() + 1;
+      format!(
+        "{}{} {}{}\n",
+        indent,
+        prefix,
+        wrap_generic(ts_type, indent, column + suffix.len(), max_line_length),
+        suffix
       )
-      .as_str(),
+    }
+  }
+
+  fn format_field_type(&self, field: &FieldDefinition) -> String {
+    let key_pattern = self
+      .options
+      .key_pattern_annotation
+      .as_ref()
+      .and_then(|key| {
+        field
+          .annotations
+          .as_ref()?
+          .annotations
+          .iter()
+          .find(|annotation| &annotation.name.value == key)
+      });
+
+    match (&field.field_type, key_pattern) {
+      (ThriftType::Map(key_ty, value_ty), Some(key_pattern)) if **key_ty == ThriftType::String => {
+        let pattern = key_pattern
+          .value
+          .value
+          .replace('\\', "\\\\")
+          .replace('`', "\\`")
+          .replace('$', "\\$")
+          .replace('*', "${string}");
+
+        format!(
+          "Record<`{}`, {}>",
+          pattern,
+          self.format_thrift_type(value_ty)
+        )
+      }
+      _ => self.format_thrift_type(&field.field_type),
+    }
+  }
+
+  /// 字段在 TS 类型中的名称，`field_name` 为传输时使用的名称
+  fn ts_field_name(&self, field: &FieldDefinition) -> String {
+    match self.options.field_naming {
+      FieldNaming::Original => field_name(field),
+      FieldNaming::CamelCase => to_camel_case(&field_name(field)),
+    }
+  }
+
+  /// 是否需要调用其他 struct 的编解码函数
+  fn needs_codec(&self, thrift_type: &ThriftType) -> bool {
+    match thrift_type {
+      ThriftType::List(ty) => self.needs_codec(ty),
+      ThriftType::Map(_, value_ty) => self.needs_codec(value_ty),
+      ThriftType::Identifier(id) => self.struct_names.contains(&id.value),
+      _ => false,
+    }
+  }
+
+  /// `prefix` 为 `encode` 或 `decode`
+  fn format_codec_expr(&self, thrift_type: &ThriftType, expr: &str, prefix: &str) -> String {
+    match thrift_type {
+      ThriftType::List(ty) if self.needs_codec(ty) => format!(
+        "{}.map((v) => {})",
+        expr,
+        self.format_codec_expr(ty, "v", prefix)
+      ),
+      ThriftType::Map(_, value_ty) if self.needs_codec(value_ty) => format!(
+        "Object.fromEntries(Object.entries({}).map(([k, v]) => [k, {}]))",
+        expr,
+        self.format_codec_expr(value_ty, "v", prefix)
+      ),
+      ThriftType::Identifier(id) if self.struct_names.contains(&id.value) => {
+        format!("{}{}({})", prefix, id.value, expr)
+      }
+      _ => expr.to_string(),
+    }
+  }
+
+  /// 声明了 `throws` 的函数额外生成结果类型及其解码函数，结果中的字段以字段 id 为 key，
+  /// 返回值固定为 0，其余为各个异常的字段 id
+  fn format_result_decoder(&self, function_definition: &FunctionDefinition) -> String {
+    let name = format!("{}Result", function_definition.name.value);
+    let mut fields = vec![];
+
+    if function_definition.return_type != ThriftType::Void {
+      fields.push(("success".to_string(), 0, &function_definition.return_type));
+    }
+
+    for field in &function_definition.throws {
+      fields.push((
+        self.ts_field_name(field),
+        field.field_id.value,
+        &field.field_type,
+      ));
+    }
+
+    let mut type_code = format!("\nexport interface {} {{\n", name);
+    let mut decode_code = format!(
+      "\nexport function decode{}(json: Record<string, any>): {} {{\n{}return {{\n",
+      name, name, INDENT
     );
 
-    if let Some(annotations) = &function_definition.annotations {
-      let mut api_method = None;
-      let mut api_path = None;
+    for (ts_name, field_id, field_type) in fields {
+      type_code.push_str(
+        format!(
+          "{}{}?: {};\n",
+          INDENT,
+          property_key(&ts_name),
+          self.format_thrift_type(field_type)
+        )
+        .as_str(),
+      );
 
-      for annotation in &annotations.annotations {
-        match annotation.name.value.as_str() {
-          "api.get" => {
-            api_method = Some("GET".to_string());
-          }
-          "api.post" => {
-            api_method = Some("POST".to_string());
-          }
-          "api.put" => {
-            api_method = Some("PUT".to_string());
-          }
-          "api.delete" => {
-            api_method = Some("DELETE".to_string());
-          }
-          _ => {}
-        }
+      let from = property_access("json", &field_id.to_string());
+      let mut expr = self.format_codec_expr(field_type, &from, "decode");
 
-        if api_method.is_some() && api_path.is_none() {
-          api_path = Some(annotation.value.value.clone());
-        }
+      if expr != from {
+        expr = format!("{} == null ? {} : {}", from, from, expr);
       }
 
-      if let Some(api_method) = api_method {
-        let mut has_query = false;
-        let mut has_body = false;
+      decode_code.push_str(
+        format!(
+          "{}{}{}: {},\n",
+          INDENT,
+          INDENT,
+          property_key(&ts_name),
+          expr
+        )
+        .as_str(),
+      );
+    }
 
-        if let Some(first_arg_name) = first_arg_name {
-          match api_method.as_str() {
-            "GET" => {
-              has_query = true;
-              code.push_str(INDENT);
-              code.push_str(
-                format!(
-                  "const query = new URLSearchParams({}).toString();\n",
-                  first_arg_name
-                )
-                .as_str(),
-              )
-            }
-            _ => {
-              has_body = true;
-              code.push_str(INDENT);
-              code.push_str(format!("const body = JSON.stringify({});\n", first_arg_name).as_str());
-            }
-          }
+    type_code.push_str("}\n");
+    decode_code.push_str(INDENT);
+    decode_code.push_str("};\n}\n");
+
+    type_code + &decode_code
+  }
+
+  /// 是否生成编解码函数，`CamelCase` 时请求函数依赖它们转换字段名
+  fn json_codecs(&self) -> bool {
+    self.options.emit_json_codecs || self.options.field_naming == FieldNaming::CamelCase
+  }
+
+  fn format_json_codecs(&self, name: &str, fields: &[FieldDefinition]) -> String {
+    let mut encode_code = format!(
+      "\nexport function encode{}(value: {}): Record<string, any> {{\n{}return {{\n",
+      name, name, INDENT
+    );
+    let mut decode_code = format!(
+      "\nexport function decode{}(json: Record<string, any>): {} {{\n{}return {{\n",
+      name, name, INDENT
+    );
+
+    for field in fields {
+      let wire_name = field_name(field);
+      let ts_name = self.ts_field_name(field);
+
+      for (code, from, to, prefix) in [
+        (
+          &mut encode_code,
+          property_access("value", &ts_name),
+          &wire_name,
+          "encode",
+        ),
+        (
+          &mut decode_code,
+          property_access("json", &wire_name),
+          &ts_name,
+          "decode",
+        ),
+      ] {
+        let mut expr = self.format_codec_expr(&field.field_type, &from, prefix);
+
+        // 缺失的字段原样保留，不调用编解码函数
+        if expr != from {
+          expr = format!("{} == null ? {} : {}", from, from, expr);
         }
 
-        code.push_str(INDENT);
+        code.push_str(format!("{}{}{}: {},\n", INDENT, INDENT, property_key(to), expr).as_str());
+      }
+    }
 
-        if has_query {
-          code.push_str(
-            format!(
-              "const url = `${{urlPrefix}}{}?${{query}}`;\n",
-              api_path.unwrap()
-            )
-            .as_str(),
-          );
+    for code in [&mut encode_code, &mut decode_code] {
+      code.push_str(INDENT);
+      code.push_str("};\n}\n");
+    }
+
+    encode_code + &decode_code
+  }
+
+  /// 可辨识联合类型的编解码函数，编码时去掉判别字段，解码时根据有值的字段补上判别字段
+  fn format_discriminated_json_codecs(
+    &self,
+    name: &str,
+    discriminator: &str,
+    fields: &[FieldDefinition],
+  ) -> String {
+    let mut encode_code = format!(
+      "\nexport function encode{}(value: {}): Record<string, any> {{\n{}switch ({}) {{\n",
+      name,
+      name,
+      INDENT,
+      property_access("value", discriminator)
+    );
+    let mut decode_code = format!(
+      "\nexport function decode{}(json: Record<string, any>): {} {{\n",
+      name, name
+    );
+
+    for field in fields {
+      let wire_name = field_name(field);
+      let ts_name = self.ts_field_name(field);
+      let from = property_access("value", &ts_name);
+      let encode_expr = self.format_codec_expr(&field.field_type, &from, "encode");
+
+      encode_code.push_str(
+        format!(
+          "{0}{0}case '{1}':\n{0}{0}{0}return {{ {2}: {3} }};\n",
+          INDENT,
+          ts_name,
+          property_key(&wire_name),
+          encode_expr
+        )
+        .as_str(),
+      );
+
+      let from = property_access("json", &wire_name);
+      let decode_expr = self.format_codec_expr(&field.field_type, &from, "decode");
+
+      decode_code.push_str(
+        format!(
+          "{0}if ({1} != null) {{\n{0}{0}return {{ {2}: '{3}', {4}: {5} }};\n{0}}}\n",
+          INDENT,
+          from,
+          property_key(discriminator),
+          ts_name,
+          property_key(&ts_name),
+          decode_expr
+        )
+        .as_str(),
+      );
+    }
+
+    encode_code.push_str(INDENT);
+    encode_code.push_str("}\n}\n");
+    decode_code.push_str(
+      format!(
+        "{}throw new Error('Invalid {}: none of the members is set');\n}}\n",
+        INDENT, name
+      )
+      .as_str(),
+    );
+
+    encode_code + &decode_code
+  }
+
+  fn format_zod_type(&self, thrift_type: &ThriftType) -> String {
+    match thrift_type {
+      ThriftType::Void => "z.void()".to_string(),
+      ThriftType::String => "z.string()".to_string(),
+      ThriftType::I16 | ThriftType::I32 => "z.number().int()".to_string(),
+      ThriftType::I64 => {
+        if self.options.i64_as_string {
+          "z.string()".to_string()
         } else {
-          code.push_str(format!("const url = `${{urlPrefix}}{}`;\n", api_path.unwrap()).as_str());
+          "z.number().int()".to_string()
         }
+      }
+      ThriftType::Double => "z.number()".to_string(),
+      ThriftType::Bool => "z.boolean()".to_string(),
+      ThriftType::List(ty) => format!("z.array({})", self.format_zod_type(ty)),
+      // JSON 对象的 key 总是字符串
+      ThriftType::Map(_, value_ty) => {
+        format!("z.record(z.string(), {})", self.format_zod_type(value_ty))
+      }
+      // 使用 z.lazy 避免引用后面才定义的 schema
+      ThriftType::Identifier(id) => format!("z.lazy(() => {}Schema)", id.value),
+    }
+  }
 
-        // return fetch(uri, { method, headers }, option);
-        code.push_str(INDENT);
+  fn format_zod_object(
+    &self,
+    name: &str,
+    fields: &[FieldDefinition],
+    force_optional: bool,
+  ) -> String {
+    let mut code = format!("\nexport const {}Schema = z.object({{\n", name);
 
-        if has_body {
-          code.push_str(
-            format!(
-              "return fetch(url, {{ method: '{}', body }}, options);\n",
-              api_method
-            )
-            .as_str(),
-          );
+    for field in fields {
+      let mut schema = self.format_zod_type(&field.field_type);
+      let is_optional = matches!(field.requiredness, Some(Requiredness::Optional));
+
+      if is_optional && self.options.optional_null {
+        schema.push_str(".nullish()");
+      } else if is_optional || force_optional {
+        schema.push_str(".optional()");
+      }
+
+      code.push_str(
+        format!(
+          "{}{}: {},\n",
+          INDENT,
+          property_key(&self.ts_field_name(field)),
+          schema
+        )
+        .as_str(),
+      );
+    }
+
+    code.push_str("});\n");
+    code
+  }
+
+  /// 可辨识联合类型的 schema，每个成员都带有判别字段
+  fn format_zod_discriminated_union(
+    &self,
+    name: &str,
+    discriminator: &str,
+    fields: &[FieldDefinition],
+  ) -> String {
+    let mut code = format!(
+      "\nexport const {}Schema = z.discriminatedUnion('{}', [\n",
+      name, discriminator
+    );
+
+    for field in fields {
+      let ts_name = self.ts_field_name(field);
+
+      code.push_str(
+        format!(
+          "{}z.object({{ {}: z.literal('{}'), {}: {} }}),\n",
+          INDENT,
+          property_key(discriminator),
+          ts_name,
+          property_key(&ts_name),
+          self.format_zod_type(&field.field_type)
+        )
+        .as_str(),
+      );
+    }
+
+    code.push_str("]);\n");
+    code
+  }
+
+  /// 运行时访问 enum 成员使用的名称，`EnumStyle::ConstObject` 时会带上后缀
+  fn enum_object_name(&self, name: &str) -> String {
+    match (self.options.enum_style, &self.options.enum_object_suffix) {
+      (EnumStyle::ConstObject, Some(suffix)) => format!("{}{}", name, suffix),
+      _ => name.to_string(),
+    }
+  }
+
+  /// 检查值是否符合类型的表达式，引用的 struct 只检查是否为对象，无法判断的类型返回 `None`
+  fn format_guard_check(&self, thrift_type: &ThriftType, expr: &str) -> Option<String> {
+    let type_of = |name: &str| Some(format!("typeof {} === '{}'", expr, name));
+
+    match thrift_type {
+      ThriftType::Void => None,
+      ThriftType::String => type_of("string"),
+      ThriftType::I16 | ThriftType::I32 | ThriftType::Double => type_of("number"),
+      ThriftType::I64 => type_of(if self.options.i64_as_string {
+        "string"
+      } else {
+        "number"
+      }),
+      ThriftType::Bool => type_of("boolean"),
+      ThriftType::List(_) => Some(format!("Array.isArray({})", expr)),
+      ThriftType::Map(..) => Some(format!("typeof {0} === 'object' && {0} !== null", expr)),
+      ThriftType::Identifier(id) if self.enum_names.contains(&id.value) => {
+        type_of(if self.options.enum_style == EnumStyle::StringEnum {
+          "string"
         } else {
-          code.push_str(
-            format!(
-              "return fetch(url, {{ method: '{}' }}, options);\n",
-              api_method
-            )
-            .as_str(),
-          );
+          "number"
+        })
+      }
+      ThriftType::Identifier(id) if self.struct_names.contains(&id.value) => {
+        Some(format!("typeof {0} === 'object' && {0} !== null", expr))
+      }
+      ThriftType::Identifier(_) => None,
+    }
+  }
+
+  fn format_struct_guard(&self, struct_definition: &StructDefinition) -> String {
+    let name = &struct_definition.name.value;
+    let field_checks = struct_definition
+      .fields
+      .iter()
+      .filter(|field| !matches!(field.requiredness, Some(Requiredness::Optional)))
+      .map(|field| {
+        let expr = property_access("o", &self.ts_field_name(field));
+        self
+          .format_guard_check(&field.field_type, &expr)
+          .unwrap_or_else(|| format!("{} !== undefined", expr))
+      })
+      .collect::<Vec<_>>();
+
+    let mut code = format!(
+      "\nexport function is{0}(v: unknown): v is {0} {{\n{1}if (typeof v !== 'object' || v === null) {{\n{1}{1}return false;\n{1}}}\n",
+      name, INDENT
+    );
+
+    if field_checks.is_empty() {
+      code.push_str(format!("{}return true;\n}}\n", INDENT).as_str());
+      return code;
+    }
+
+    code.push_str(format!("{}const o = v as Record<string, unknown>;\n", INDENT).as_str());
+    code.push_str(format!("{}return (\n", INDENT).as_str());
+    code.push_str(
+      field_checks
+        .iter()
+        .map(|check| format!("{}{}{}", INDENT, INDENT, check))
+        .collect::<Vec<_>>()
+        .join(" &&\n")
+        .as_str(),
+    );
+    code.push_str(format!("\n{});\n}}\n", INDENT).as_str());
+
+    code
+  }
+
+  /// `struct_extends_annotation` 指定的基类型，与字段类型一样解析，引用 include 中的类型时使用 `a.Base`
+  fn struct_extends(&self, struct_definition: &StructDefinition) -> Option<String> {
+    let key = self.options.struct_extends_annotation.as_ref()?;
+    let annotation = struct_definition
+      .annotations
+      .as_ref()?
+      .annotations
+      .iter()
+      .find(|annotation| &annotation.name.value == key)?;
+
+    Some(self.format_thrift_type(&ThriftType::Identifier(Identifier {
+      value: annotation.value.value.trim().to_string(),
+    })))
+  }
+
+  fn format_enum_validator(&self, enum_definition: &EnumDefinition) -> String {
+    let mut values = vec![];
+
+    let member_values = if self.options.enum_style == EnumStyle::StringEnum {
+      enum_definition
+        .members
+        .iter()
+        .map(|member| format!("'{}'", member.name.value))
+        .collect::<Vec<_>>()
+    } else {
+      enum_definition
+        .member_values()
+        .iter()
+        .map(|value| value.to_string())
+        .collect()
+    };
+
+    for value in member_values {
+      if !values.contains(&value) {
+        values.push(value);
+      }
+    }
+
+    let checks = if values.is_empty() {
+      "false".to_string()
+    } else {
+      values
+        .iter()
+        .map(|value| format!("v === {}", value))
+        .collect::<Vec<_>>()
+        .join(" || ")
+    };
+
+    format!(
+      "\nexport function is{0}(v: unknown): v is {0} {{\n{1}return {2};\n}}\n",
+      enum_definition.name.value, INDENT, checks
+    )
+  }
+
+  fn format_enum_match(&self, enum_definition: &EnumDefinition) -> String {
+    let name = &enum_definition.name.value;
+    let mut code = format!("\nexport function match{}<T>(\n", name);
+
+    code.push_str(format!("{}v: {},\n{}handlers: {{\n", INDENT, name, INDENT).as_str());
+
+    for member in &enum_definition.members {
+      code.push_str(format!("{}{}{}: () => T;\n", INDENT, INDENT, member.name.value).as_str());
+    }
+
+    code.push_str(format!("{}}},\n): T {{\n{}switch (v) {{\n", INDENT, INDENT).as_str());
+
+    for member in &enum_definition.members {
+      code.push_str(
+        format!(
+          "{0}{0}case {1}.{2}:\n{0}{0}{0}return handlers.{2}();\n",
+          INDENT,
+          self.enum_object_name(name),
+          member.name.value
+        )
+        .as_str(),
+      );
+    }
+
+    code.push_str(
+      format!(
+        "{0}{0}default: {{\n{0}{0}{0}const _exhaustive: never = v;\n{0}{0}{0}throw new Error(`Unknown {1}: ${{_exhaustive}}`);\n{0}{0}}}\n{0}}}\n}}\n",
+        INDENT, name
+      )
+      .as_str(),
+    );
+
+    code
+  }
+
+  /// 函数参数列表，如 `req: GetDataReq`，可选参数带有 `?`
+  /// 函数的参数列表，必选参数不能跟在可选参数之后，
+  /// 所以只有之后的参数都是可选的时候才使用 `a?: A`，否则使用 `a: A | undefined`
+  fn format_params(&self, function_definition: &FunctionDefinition) -> Vec<String> {
+    let fields = &function_definition.fields;
+    // 从这个下标开始的参数都是可选的
+    let optional_from = fields
+      .iter()
+      .rposition(|field| !matches!(field.requiredness, Some(Requiredness::Optional)))
+      .map_or(0, |index| index + 1);
+
+    fields
+      .iter()
+      .enumerate()
+      .map(|(index, field)| {
+        let field_type = self.format_thrift_type(&field.field_type);
+
+        if index >= optional_from {
+          format!("{}?: {}", field.name.value, field_type)
+        } else if matches!(field.requiredness, Some(Requiredness::Optional)) {
+          format!("{}: {} | undefined", field.name.value, field_type)
+        } else {
+          format!("{}: {}", field.name.value, field_type)
         }
+      })
+      .collect()
+  }
 
-        code.push_str("}\n");
-        self.code.push_str(code.as_str());
-        return;
+  /// 函数名首字母小写作为 key，只有一个参数时直接使用参数的类型，多个参数时合并为对象
+  fn format_method_map(&self, service_definition: &ServiceDefinition) -> String {
+    let mut code = format!("\nexport type {}Methods = ", service_definition.name.value);
+
+    if let Some(extends) = &service_definition.extends {
+      code.push_str(format!("{}Methods & ", extends.value).as_str());
+    }
+
+    code.push_str("{\n");
+
+    for function_definition in &service_definition.functions {
+      code.push_str(
+        format!(
+          "{}{}: {{ args: {}; result: {} }};\n",
+          INDENT,
+          property_key(&method_key(function_definition)),
+          self.format_method_args(function_definition),
+          self.format_thrift_type(&function_definition.return_type)
+        )
+        .as_str(),
+      );
+    }
+
+    code.push_str("};\n");
+    code
+  }
+
+  /// 与 `format_method_map` 相同的 key 和参数类型，只包含 oneway 函数
+  fn format_events_map(&self, service_definition: &ServiceDefinition) -> String {
+    let mut code = format!("\nexport type {}Events = ", service_definition.name.value);
+
+    if let Some(extends) = &service_definition.extends {
+      code.push_str(format!("{}Events & ", extends.value).as_str());
+    }
+
+    code.push_str("{\n");
+
+    for function_definition in &service_definition.functions {
+      if !function_definition.oneway {
+        continue;
       }
+
+      code.push_str(
+        format!(
+          "{}{}: {};\n",
+          INDENT,
+          property_key(&method_key(function_definition)),
+          self.format_method_args(function_definition)
+        )
+        .as_str(),
+      );
     }
 
-    code.push_str(INDENT);
-    code.push_str("throw new Error('not implemented');\n}}\n");
+    code.push_str("};\n");
+    code
+  }
+
+  /// 只有一个参数时直接使用参数的类型，多个参数时合并为对象
+  fn format_method_args(&self, function_definition: &FunctionDefinition) -> String {
+    match &function_definition.fields[..] {
+      [] => "void".to_string(),
+      [field] => self.format_thrift_type(&field.field_type),
+      // 对象类型中的属性没有顺序限制，可选参数都使用 `a?: A`
+      fields => format!(
+        "{{ {} }}",
+        fields
+          .iter()
+          .map(|field| format!(
+            "{}{}: {}",
+            field.name.value,
+            if matches!(field.requiredness, Some(Requiredness::Optional)) {
+              "?"
+            } else {
+              ""
+            },
+            self.format_thrift_type(&field.field_type)
+          ))
+          .collect::<Vec<_>>()
+          .join("; ")
+      ),
+    }
+  }
+
+  fn format_service_output(
+    &self,
+    service_definition: &ServiceDefinition,
+    service_output: ServiceOutput,
+  ) -> String {
+    let name = &service_definition.name.value;
+    let mut code = String::new();
+
+    let format_methods = |code: &mut String, prefix: &str| {
+      for function_definition in &service_definition.functions {
+        code.push_str(&self.format_comments(&function_definition.comments, INDENT));
+        code.push_str(
+          format!(
+            "{}{}{}({}): Promise<{}>;\n",
+            INDENT,
+            prefix,
+            function_definition.name.value,
+            self.format_params(function_definition).join(", "),
+            self.format_thrift_type(&function_definition.return_type)
+          )
+          .as_str(),
+        );
+      }
+    };
+
+    if matches!(
+      service_output,
+      ServiceOutput::Interface | ServiceOutput::Both
+    ) {
+      code.push('\n');
+      code.push_str(&self.format_comments(&service_definition.comments, ""));
+      code.push_str(format!("export interface {}", name).as_str());
+
+      if let Some(extends) = &service_definition.extends {
+        code.push_str(format!(" extends {}", extends.value).as_str());
+      }
+
+      code.push_str(" {\n");
+      format_methods(&mut code, "");
+      code.push_str("}\n");
+    }
+
+    if matches!(
+      service_output,
+      ServiceOutput::AbstractClass | ServiceOutput::Both
+    ) {
+      code.push('\n');
+      code.push_str(&self.format_comments(&service_definition.comments, ""));
+      code.push_str(format!("export abstract class {}Base", name).as_str());
+
+      if let Some(extends) = &service_definition.extends {
+        code.push_str(format!(" extends {}Base", extends.value).as_str());
+      }
+
+      if service_output == ServiceOutput::Both {
+        code.push_str(format!(" implements {}", name).as_str());
+      }
+
+      code.push_str(" {\n");
+      format_methods(&mut code, "abstract ");
+      code.push_str("}\n");
+    }
+
+    code
+  }
+
+  /// 开启 `const_as_const` 时，只有字面量、list 和 map 可以追加 `as const`，
+  /// 引用其他常量或 enum 成员（如 `const i32 B = A`）时 TS 不允许使用 `as const`
+  fn const_as_const(&self, value: &ConstValue) -> bool {
+    self.options.const_as_const && !matches!(value, ConstValue::Identifier(_))
+  }
+
+  /// 常量的类型声明，只开启 `const_as_const` 时不声明类型，由 TS 推导出字面量类型
+  fn format_const_type(&self, const_definition: &ConstDefinition) -> Option<String> {
+    let field_type = &const_definition.field_type;
+
+    match (&const_definition.value, field_type) {
+      // 元组的长度与常量中的元素个数一致
+      (ConstValue::List(items), ThriftType::List(ty))
+        if self.options.const_readonly && self.options.const_as_const =>
+      {
+        Some(format!(
+          "readonly [{}]",
+          vec![self.format_thrift_type(ty); items.len()].join(", ")
+        ))
+      }
+      (_, ThriftType::List(ty)) if self.options.const_readonly => {
+        Some(format!("ReadonlyArray<{}>", self.format_thrift_type(ty)))
+      }
+      (_, ThriftType::Map(..)) if self.options.const_readonly => {
+        Some(format!("Readonly<{}>", self.format_thrift_type(field_type)))
+      }
+      (value, _) if self.const_as_const(value) => None,
+      _ => Some(self.format_thrift_type(field_type)),
+    }
+  }
+
+  fn format_const_value(&self, value: &ConstValue, thrift_type: &ThriftType) -> String {
+    match value {
+      ConstValue::Integer(v) => {
+        if self.options.i64_as_string && *thrift_type == ThriftType::I64 {
+          format!("'{}'", v)
+        } else {
+          v.to_string()
+        }
+      }
+      ConstValue::Double(v) => v.to_string(),
+      ConstValue::String(v) => format!("'{}'", v.value.replace('\\', "\\\\").replace('\'', "\\'")),
+      ConstValue::Identifier(id) => match id.value.split_once('.') {
+        Some((enum_name, member)) if self.enum_names.contains(enum_name) => {
+          format!("{}.{}", self.enum_object_name(enum_name), member)
+        }
+        _ => id.value.clone(),
+      },
+      ConstValue::List(items) => {
+        let item_type = match thrift_type {
+          ThriftType::List(ty) => ty.as_ref(),
+          _ => thrift_type,
+        };
+        let items = items
+          .iter()
+          .map(|item| self.format_const_value(item, item_type))
+          .collect::<Vec<_>>();
+
+        format!("[{}]", items.join(", "))
+      }
+      ConstValue::Map(entries) => {
+        if entries.is_empty() {
+          return "{}".to_string();
+        }
+
+        let (key_type, value_type) = match thrift_type {
+          ThriftType::Map(key_ty, value_ty) => (key_ty.as_ref(), value_ty.as_ref()),
+          _ => (thrift_type, thrift_type),
+        };
+        let entries = entries
+          .iter()
+          .map(|(key, value)| {
+            let key_code = self.format_const_value(key, key_type);
+            // 引用作为 key 时需要使用计算属性
+            let key_code = match key {
+              ConstValue::Identifier(_) => format!("[{}]", key_code),
+              _ => key_code,
+            };
+
+            format!(
+              "{}: {}",
+              key_code,
+              self.format_const_value(value, value_type)
+            )
+          })
+          .collect::<Vec<_>>();
+
+        format!("{{ {} }}", entries.join(", "))
+      }
+    }
+  }
+
+  fn format_thrift_type(&self, thrift_type: &ThriftType) -> String {
+    match &thrift_type {
+      ThriftType::Void => "void".to_string(),
+      ThriftType::String => "string".to_string(),
+      ThriftType::I16 => "number".to_string(),
+      ThriftType::I32 => "number".to_string(),
+      ThriftType::I64 => {
+        if self.options.i64_as_string {
+          "string".to_string()
+        } else {
+          "number".to_string()
+        }
+      }
+      ThriftType::Double => "number".to_string(),
+      ThriftType::Bool => "boolean".to_string(),
+      ThriftType::List(ty) => format!("Array<{}>", self.format_thrift_type(ty)),
+      ThriftType::Map(key_ty, value_ty) => {
+        let record = format!(
+          "Record<{}, {}>",
+          self.format_thrift_type(key_ty),
+          self.format_thrift_type(value_ty)
+        );
+
+        // key 为 enum 时不一定包含所有成员
+        match key_ty.as_ref() {
+          ThriftType::Identifier(id) if self.enum_names.contains(&id.value) => {
+            format!("Partial<{}>", record)
+          }
+          _ => record,
+        }
+      }
+      ThriftType::Identifier(id) => id.value.clone(),
+    }
+  }
+}
+
+impl Visit for Visitor {
+  fn visit_include_definition(&mut self, include_definition: &mut IncludeDefinition) {
+    let path = Path::new(&include_definition.path.value)
+      .file_stem()
+      .unwrap()
+      .to_str()
+      .unwrap();
+    let alias = self
+      .include_aliases
+      .get(&include_definition.path.value)
+      .map(|alias| alias.as_str())
+      .unwrap_or(path);
+    let import_path = self
+      .include_paths
+      .get(&include_definition.path.value)
+      .cloned()
+      .unwrap_or(format!("./{}", path));
+    let import_path = match &self.options.import_extension {
+      Some(extension) if import_path.starts_with('.') => import_path + extension,
+      _ => import_path,
+    };
+    let mut code = match self.options.module_kind {
+      ModuleKind::Esm => format!("import * as {} from '{}';\n", alias, import_path),
+      ModuleKind::CommonJs => format!("import {} = require('{}');\n", alias, import_path),
+    };
+
+    if self.options.reexport_includes {
+      code.push_str(format!("export {{ {} }};\n", alias).as_str());
+
+      // 为引用的类型生成别名，使用方可以直接通过当前模块访问，不需要知道类型来自哪个模块
+      for qualified_ref in &self.qualified_refs {
+        if let Some((qualifier, name)) = qualified_ref.rsplit_once('.') {
+          if qualifier == alias {
+            code.push_str(format!("export import {} = {};\n", name, qualified_ref).as_str());
+          }
+        }
+      }
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_struct_definition(&mut self, struct_definition: &mut StructDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(
+      self
+        .format_comments(&struct_definition.comments, "")
+        .as_str(),
+    );
+    code.push_str(format!("export interface {}", struct_definition.name.value).as_str());
+
+    if let Some(base) = self.struct_extends(struct_definition) {
+      code.push_str(format!(" extends {}", base).as_str());
+    }
+
+    code.push_str(" {\n");
+
+    for field in &struct_definition.fields {
+      code.push_str(&self.format_field(field, INDENT));
+    }
+
+    code.push_str("}\n");
+
+    if self.options.emit_partial_variants
+      && self.request_structs.contains(&struct_definition.name.value)
+    {
+      let name = self
+        .options
+        .partial_variant_name
+        .replace("{name}", &struct_definition.name.value);
+
+      code.push('\n');
+      code.push_str(format!("export interface {} {{\n", name).as_str());
+
+      for field in &struct_definition.fields {
+        code.push_str(&self.format_field_with_optional(field, INDENT, true));
+      }
+
+      code.push_str("}\n");
+    }
+
+    if self.json_codecs() {
+      code.push_str(
+        &self.format_json_codecs(&struct_definition.name.value, &struct_definition.fields),
+      );
+    }
+
+    if self.options.validation_target == Some(ValidationTarget::Zod) {
+      code.push_str(&self.format_zod_object(
+        &struct_definition.name.value,
+        &struct_definition.fields,
+        false,
+      ));
+    }
+
+    if self.options.emit_struct_guards {
+      code.push_str(&self.format_struct_guard(struct_definition));
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_union_definition(&mut self, union_definition: &mut UnionDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(&self.format_comments(&union_definition.comments, ""));
+
+    let discriminator = self
+      .options
+      .discriminator_annotation
+      .as_ref()
+      .and_then(|key| {
+        union_definition
+          .annotations
+          .as_ref()?
+          .annotations
+          .iter()
+          .find(|annotation| &annotation.name.value == key)
+      });
+
+    if let Some(discriminator) = discriminator {
+      code.push_str(format!("export type {} =\n", union_definition.name.value).as_str());
+
+      for field in self.ordered(union_definition.fields.iter().collect(), |field| {
+        self.ts_field_name(field)
+      }) {
+        let name = self.ts_field_name(field);
+
+        code.push_str(
+          format!(
+            "{}| {{ {}: '{}'; {}: {} }}\n",
+            INDENT,
+            property_key(&discriminator.value.value),
+            name,
+            property_key(&name),
+            self.format_field_type(field)
+          )
+          .as_str(),
+        );
+      }
+
+      code.pop();
+      code.push_str(";\n");
+    } else {
+      code.push_str(format!("export interface {} {{\n", union_definition.name.value).as_str());
+
+      // 同一时刻只会有一个字段有值，所以所有字段都是可选的
+      for field in self.ordered(union_definition.fields.iter().collect(), |field| {
+        self.ts_field_name(field)
+      }) {
+        code.push_str(&self.format_field_with_optional(field, INDENT, true));
+      }
+
+      code.push_str("}\n");
+    }
+
+    let name = &union_definition.name.value;
+    let fields = &union_definition.fields;
+
+    // 可辨识联合类型的结构与普通 union 不同，编解码函数和 schema 需要处理判别字段
+    if self.json_codecs() {
+      code.push_str(&match discriminator {
+        Some(discriminator) => {
+          self.format_discriminated_json_codecs(name, &discriminator.value.value, fields)
+        }
+        None => self.format_json_codecs(name, fields),
+      });
+    }
+
+    if self.options.validation_target == Some(ValidationTarget::Zod) {
+      code.push_str(&match discriminator {
+        Some(discriminator) => {
+          self.format_zod_discriminated_union(name, &discriminator.value.value, fields)
+        }
+        None => self.format_zod_object(name, fields, true),
+      });
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_enum_definition(&mut self, enum_definition: &mut EnumDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(self.format_comments(&enum_definition.comments, "").as_str());
+
+    let members = self.ordered(
+      enum_definition
+        .members
+        .iter()
+        .zip(enum_definition.member_values())
+        .collect(),
+      |(member, _)| member.name.value.clone(),
+    );
+
+    match self.options.enum_style {
+      EnumStyle::Enum | EnumStyle::ConstEnum => {
+        let keyword = if self.options.enum_style == EnumStyle::ConstEnum {
+          "const enum"
+        } else {
+          "enum"
+        };
+        code.push_str(format!("export {} {} {{\n", keyword, enum_definition.name.value).as_str());
+
+        for (member, value) in &members {
+          code.push_str(&self.format_comments(&member.comments, INDENT));
+          code.push_str(INDENT);
+          code.push_str(&member.name.value);
+
+          // 调整顺序后自增的取值会发生变化，需要显式写出
+          if self.options.member_order == MemberOrder::Alphabetical {
+            code.push_str(format!(" = {}", value).as_str());
+          } else if let Some(initializer) = &member.initializer {
+            code.push_str(" = ");
+            code.push_str(&initializer.value);
+          }
+          code.push_str(",\n");
+        }
+
+        code.push_str("}\n");
+      }
+      EnumStyle::StringEnum => {
+        code.push_str(format!("export enum {} {{\n", enum_definition.name.value).as_str());
+
+        for (member, _) in &members {
+          code.push_str(&self.format_comments(&member.comments, INDENT));
+          code.push_str(format!("{0}{1} = '{1}',\n", INDENT, member.name.value).as_str());
+        }
+
+        code.push_str("}\n");
+      }
+      EnumStyle::ConstObject => {
+        let object_name = self.enum_object_name(&enum_definition.name.value);
+        code.push_str(format!("export const {} = {{\n", object_name).as_str());
+
+        // 对象中没有自增的取值，需要显式写出每个成员的值
+        for (member, value) in &members {
+          code.push_str(&self.format_comments(&member.comments, INDENT));
+          code.push_str(format!("{}{}: {},\n", INDENT, member.name.value, value).as_str());
+        }
+
+        code.push_str("} as const;\n");
+        code.push_str(
+          format!(
+            "export type {} = (typeof {1})[keyof typeof {1}];\n",
+            enum_definition.name.value, object_name
+          )
+          .as_str(),
+        );
+      }
+    }
+
+    if self.options.emit_enum_match {
+      code.push_str(&self.format_enum_match(enum_definition));
+    }
+
+    if self.options.emit_enum_validators {
+      code.push_str(&self.format_enum_validator(enum_definition));
+    }
+
+    if self.options.validation_target == Some(ValidationTarget::Zod) {
+      // const enum 没有运行时对象，无法传给 z.nativeEnum，改为检查取值
+      let schema = if self.options.enum_style == EnumStyle::ConstEnum {
+        let values = enum_definition
+          .member_values()
+          .iter()
+          .map(|value| value.to_string())
+          .collect::<Vec<_>>();
+
+        format!(
+          "z.number().refine((v): v is {} => [{}].includes(v))",
+          enum_definition.name.value,
+          values.join(", ")
+        )
+      } else {
+        format!(
+          "z.nativeEnum({})",
+          self.enum_object_name(&enum_definition.name.value)
+        )
+      };
+
+      code.push_str(
+        format!(
+          "\nexport const {}Schema = {};\n",
+          enum_definition.name.value, schema
+        )
+        .as_str(),
+      );
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_senum_definition(&mut self, senum_definition: &mut SenumDefinition) {
+    let mut code = "\n".to_string();
+    let values = senum_definition
+      .values
+      .iter()
+      .map(|value| self.format_const_value(&ConstValue::String(value.clone()), &ThriftType::String))
+      .collect::<Vec<_>>();
+    let ts_type = if values.is_empty() {
+      "never".to_string()
+    } else {
+      values.join(" | ")
+    };
+
+    code.push_str(&self.format_comments(&senum_definition.comments, ""));
+    code.push_str(&self.format_type_line(
+      "",
+      &format!("export type {} =", senum_definition.name.value),
+      &ts_type,
+      ";",
+    ));
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_typedef_definition(&mut self, typedef_definition: &mut TypedefDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(&self.format_comments(&typedef_definition.comments, ""));
+    code.push_str(&self.format_type_line(
+      "",
+      &format!("export type {} =", typedef_definition.name.value),
+      &self.format_thrift_type(&typedef_definition.field_type),
+      ";",
+    ));
+
+    if self.options.validation_target == Some(ValidationTarget::Zod) {
+      code.push_str(
+        format!(
+          "\nexport const {}Schema = {};\n",
+          typedef_definition.name.value,
+          self.format_zod_type(&typedef_definition.field_type)
+        )
+        .as_str(),
+      );
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_const_definition(&mut self, const_definition: &mut ConstDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(&self.format_comments(&const_definition.comments, ""));
+    code.push_str(format!("export const {}", const_definition.name.value).as_str());
+
+    if let Some(const_type) = self.format_const_type(const_definition) {
+      code.push_str(format!(": {}", const_type).as_str());
+    }
+
+    code.push_str(
+      format!(
+        " = {}{};\n",
+        self.format_const_value(&const_definition.value, &const_definition.field_type),
+        if self.const_as_const(&const_definition.value) {
+          " as const"
+        } else {
+          ""
+        }
+      )
+      .as_str(),
+    );
+
+    if let Some(group) = self.const_group(const_definition) {
+      let members = self
+        .const_groups
+        .iter()
+        .find(|(name, _)| *name == group)
+        .map(|(_, members)| members.as_slice())
+        .unwrap_or_default();
+
+      if members.last().map(|(name, _)| name) == Some(&const_definition.name.value) {
+        code.push_str(&self.format_const_group(&group, members));
+      }
+    }
+
+    self.code.push_str(&code);
+  }
+
+  fn visit_service_definition(&mut self, service_definition: &mut ServiceDefinition) {
+    if !self.service_visited {
+      self.service_visited = true;
+
+      if let Some(fetch_declaration_code) = &self.options.fetch_declaration_code {
+        self.code.push('\n');
+        self.code.push_str(fetch_declaration_code.as_str());
+        self.code.push('\n');
+      }
+
+      let options_type = self
+        .options
+        .options_type_code
+        .clone()
+        .unwrap_or("any".to_string());
+
+      self
+        .code
+        .push_str(format!("\ntype _Options = {};\n", options_type).as_str());
+
+      self.code.push_str(
+        r#"
+let urlPrefix = '';
+export function setUrlPrefix(prefix: string) {
+  urlPrefix = prefix;
+}
+"#,
+      );
+    }
+
+    for function_definition in &mut service_definition.functions {
+      self.visit_function_definition(function_definition);
+
+      if self.json_codecs() && !function_definition.throws.is_empty() {
+        let code = self.format_result_decoder(function_definition);
+        self.code.push_str(&code);
+      }
+    }
+
+    if let Some(service_output) = self.options.service_output {
+      let code = self.format_service_output(service_definition, service_output);
+      self.code.push_str(&code);
+    }
+
+    if self.options.emit_method_map {
+      let code = self.format_method_map(service_definition);
+      self.code.push_str(&code);
+    }
+
+    if self.options.emit_events_map {
+      let code = self.format_events_map(service_definition);
+      self.code.push_str(&code);
+    }
+  }
+
+  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {
+    let mut code = "\n".to_string();
+
+    code.push_str(
+      self
+        .format_comments(&function_definition.comments, "")
+        .as_str(),
+    );
+    code.push_str(format!("export function {}(", function_definition.name.value).as_str());
+
+    let first_arg = function_definition.fields.first().map(|field| {
+      let name = field.name.value.clone();
+
+      if self.options.field_naming != FieldNaming::CamelCase {
+        return name;
+      }
+
+      // TS 中的字段名与传输的字段名不同，需要先转换为传输的字段名
+      let expr = self.format_codec_expr(&field.field_type, &name, "encode");
+
+      if expr != name && matches!(field.requiredness, Some(Requiredness::Optional)) {
+        format!("{} == null ? {} : {}", name, name, expr)
+      } else {
+        expr
+      }
+    });
+
+    // 最后一个参数是可选的时候，它之前的可选参数都已经是 `a?: A` 的形式
+    let has_optional_arg = function_definition
+      .fields
+      .last()
+      .is_some_and(|field| matches!(field.requiredness, Some(Requiredness::Optional)));
+
+    for param in self.format_params(function_definition) {
+      code.push_str(&param);
+      code.push_str(", ");
+    }
+
+    // 可选参数之后不能再跟必选参数，所以 options 也需要变成可选的
+    code.push_str(if has_optional_arg {
+      "options?: _Options"
+    } else {
+      "options: _Options"
+    });
+
+    code.push_str(
+      format!(
+        "): Promise<{}> {{\n",
+        self.format_thrift_type(&function_definition.return_type)
+      )
+      .as_str(),
+    );
+
+    if let Some(annotations) = &function_definition.annotations {
+      let mut api_method = None;
+      let mut api_path = None;
+
+      for annotation in &annotations.annotations {
+        match annotation.name.value.as_str() {
+          "api.get" => {
+            api_method = Some("GET".to_string());
+          }
+          "api.post" => {
+            api_method = Some("POST".to_string());
+          }
+          "api.put" => {
+            api_method = Some("PUT".to_string());
+          }
+          "api.delete" => {
+            api_method = Some("DELETE".to_string());
+          }
+          _ => {}
+        }
+
+        if api_method.is_some() && api_path.is_none() {
+          api_path = Some(annotation.value.value.clone());
+        }
+      }
+
+      if let Some(api_method) = api_method {
+        let mut has_query = false;
+        let mut has_body = false;
+
+        if let Some(first_arg) = first_arg {
+          match api_method.as_str() {
+            "GET" => {
+              has_query = true;
+              code.push_str(INDENT);
+              code.push_str(
+                format!(
+                  "const query = new URLSearchParams({}).toString();\n",
+                  first_arg
+                )
+                .as_str(),
+              )
+            }
+            _ => {
+              has_body = true;
+              code.push_str(INDENT);
+              code.push_str(format!("const body = JSON.stringify({});\n", first_arg).as_str());
+            }
+          }
+        }
+
+        code.push_str(INDENT);
+
+        if has_query {
+          code.push_str(
+            format!(
+              "const url = `${{urlPrefix}}{}?${{query}}`;\n",
+              api_path.unwrap()
+            )
+            .as_str(),
+          );
+        } else {
+          code.push_str(format!("const url = `${{urlPrefix}}{}`;\n", api_path.unwrap()).as_str());
+        }
+
+        // return fetch(uri, { method, headers }, option);
+        let decode = if self.options.field_naming == FieldNaming::CamelCase {
+          let expr = self.format_codec_expr(&function_definition.return_type, "res", "decode");
+
+          if expr != "res" {
+            format!(".then((res) => {})", expr)
+          } else {
+            String::new()
+          }
+        } else {
+          String::new()
+        };
+
+        code.push_str(INDENT);
+
+        if has_body {
+          code.push_str(
+            format!(
+              "return fetch(url, {{ method: '{}', body }}, options){};\n",
+              api_method, decode
+            )
+            .as_str(),
+          );
+        } else {
+          code.push_str(
+            format!(
+              "return fetch(url, {{ method: '{}' }}, options){};\n",
+              api_method, decode
+            )
+            .as_str(),
+          );
+        }
+
+        code.push_str("}\n");
+        self.code.push_str(code.as_str());
+        return;
+      }
+    }
+
+    code.push_str(INDENT);
+    code.push_str("throw new Error('not implemented');\n}\n");
+    self.code.push_str(code.as_str());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse::{Parser, MAX_TYPE_DEPTH};
+
+  const IDL: &str = r#"
+namespace x a.b.c
+
+include "a.thrift"
+
+struct GetDataReq {
+    // 这是单行注释
+    // 这也是单行注释
+    1: string parameters
+    /* 这是多行注释 */
+    2: i32 status (api.query="query_status")
+    3: double money
+    3: bool is_ok
+    2: optional map<a.A, string> kvs
+    3: required list<a.A> a_list
+    6: ItemType item_type
+}
+
+struct GetDataRes {
+    1: i32 status (api.body="body_status")
+    2: string msg
+}
+
+enum ItemType {
+    // 未知
+    Unknown = 0
+    // 普通
+    Normal = 1
+    // 特别
+    Special = 2
+}
+
+service ThriftService {
+    // 获取数据
+    GetDataRes GetData(1: GetDataReq req) (api.get = "/api/get-data", other = "something")
+}
+"#;
+
+  fn generate(idl: &str, options: GenerateOptions) -> String {
+    let mut document = Parser::new(idl).parse().unwrap();
+    Generator::new(&mut document).build(options)
+  }
+
+  #[test]
+  fn test_optional_function_arg() {
+    let code = generate(
+      r#"
+service ThriftService {
+  GetDataRes GetData(1: optional GetDataReq req) (api.get = "/api/get-data")
+}
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains(
+      "export function GetData(req?: GetDataReq, options?: _Options): Promise<GetDataRes> {"
+    ));
+
+    // 必选参数之前的可选参数不能使用 `?`
+    let code = generate(
+      r#"
+service ThriftService {
+  C F(1: optional A a, 2: B b)
+  C G(1: optional A a, 2: B b, 3: optional D d)
+}
+"#,
+      GenerateOptions {
+        service_output: Some(ServiceOutput::Interface),
+        ..Default::default()
+      },
+    );
+
+    assert!(
+      code.contains("export function F(a: A | undefined, b: B, options: _Options): Promise<C> {")
+    );
+    assert!(code.contains(
+      "export function G(a: A | undefined, b: B, d?: D, options?: _Options): Promise<C> {"
+    ));
+    assert!(code.contains("  F(a: A | undefined, b: B): Promise<C>;\n"));
+    assert!(code.contains("  G(a: A | undefined, b: B, d?: D): Promise<C>;\n"));
+  }
+
+  #[test]
+  fn test_optional_null() {
+    let code = generate(IDL, GenerateOptions::default());
+    assert!(code.contains("  kvs?: Record<a.A, string>;\n"));
+
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        optional_null: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("  kvs?: Record<a.A, string> | null;\n"));
+    assert!(code.contains("  parameters: string;\n"));
+  }
+
+  #[test]
+  fn test_field_definition_to_ts() {
+    let document = Parser::new(IDL).parse().unwrap();
+    let status = match &document.body[2] {
+      TopDefinition::Struct(struct_definition) => &struct_definition.fields[1],
+      _ => panic!(),
+    };
+
+    assert_eq!(
+      status.to_thrift(),
+      "/* 这是多行注释 */\n2: i32 status (api.query=\"query_status\")"
+    );
+    assert_eq!(
+      status.to_ts(&GenerateOptions::default()),
+      "/** 这是多行注释 */\nquery_status: number;"
+    );
+  }
+
+  #[test]
+  fn test_typedef_definition() {
+    let code = generate(
+      r#"
+enum ItemType {
+  Unknown = 0
+}
+
+// 类型列表
+typedef list<ItemType> ItemTypeList
+typedef ItemTypeList ItemTypeListAlias
+typedef map<string, ItemTypeList> ItemTypeListMap
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains("/** 类型列表 */\nexport type ItemTypeList = Array<ItemType>;\n"));
+    assert!(code.contains("export type ItemTypeListAlias = ItemTypeList;\n"));
+    assert!(code.contains("export type ItemTypeListMap = Record<string, ItemTypeList>;\n"));
+  }
+
+  #[test]
+  fn test_merge_options() {
+    let base = GenerateOptions {
+      i64_as_string: true,
+      fetch_declaration_code: Some("declare const fetch: any;".to_string()),
+      ..Default::default()
+    };
+
+    let merged = GenerateOptions::merge(
+      base,
+      PartialGenerateOptions {
+        optional_null: Some(true),
+        options_type_code: Some("RequestInit".to_string()),
+        ..Default::default()
+      },
+    );
+
+    assert!(merged.i64_as_string);
+    assert!(merged.optional_null);
+    assert_eq!(merged.options_type_code.as_deref(), Some("RequestInit"));
+    assert_eq!(
+      merged.fetch_declaration_code.as_deref(),
+      Some("declare const fetch: any;")
+    );
+  }
+
+  #[test]
+  fn test_reexport_includes() {
+    let code = generate(IDL, GenerateOptions::default());
+    assert!(!code.contains("export { a };"));
+
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        reexport_includes: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("import * as a from './a';\nexport { a };\nexport import A = a.A;\n"));
+    assert_eq!(code.matches("export import").count(), 1);
+
+    let code = generate(
+      r#"
+include "a.thrift"
+include "b.thrift"
+
+struct X {
+    1: map<a.K, list<a.V>> items
+    2: a.B b
+    3: b.V v
+}
+
+struct B {}
+"#,
+      GenerateOptions {
+        reexport_includes: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      "export { a };\nexport import K = a.K;\nexport import V = a.V;\nimport * as b from './b';\nexport { b };\n\n"
+    ));
+  }
+
+  #[test]
+  fn test_emit_enum_match() {
+    let code = generate(IDL, GenerateOptions::default());
+    assert!(!code.contains("matchItemType"));
+
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_enum_match: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export function matchItemType<T>(
+  v: ItemType,
+  handlers: {
+    Unknown: () => T;
+    Normal: () => T;
+    Special: () => T;
+  },
+): T {
+  switch (v) {
+    case ItemType.Unknown:
+      return handlers.Unknown();
+    case ItemType.Normal:
+      return handlers.Normal();
+    case ItemType.Special:
+      return handlers.Special();
+    default: {
+      const _exhaustive: never = v;
+      throw new Error(`Unknown ItemType: ${_exhaustive}`);
+    }
+  }
+}
+"#
+    ));
+  }
+
+  #[test]
+  fn test_include_aliases() {
+    let mut document = Parser::new(
+      r#"
+include "types.thrift"
+
+struct Main {
+  1: a.A item
+}
+"#,
+    )
+    .parse()
+    .unwrap();
+
+    let code = Generator::new(&mut document)
+      .include_aliases(HashMap::from([(
+        "types.thrift".to_string(),
+        "a".to_string(),
+      )]))
+      .build(GenerateOptions::default());
+
+    assert!(code.contains("import * as a from './types';\n"));
+    assert!(code.contains("  item: a.A;\n"));
+  }
+
+  #[test]
+  fn test_emit_partial_variants() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_partial_variants: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export interface PartialGetDataReq {
+  /**
+   * 这是单行注释
+   *
+   * 这也是单行注释
+   */
+  parameters?: string;
+  /** 这是多行注释 */
+  query_status?: number;
+  money?: number;
+  is_ok?: boolean;
+  kvs?: Record<a.A, string>;
+  a_list?: Array<a.A>;
+  item_type?: ItemType;
+}
+"#
+    ));
+    // GetDataRes 不是请求参数，不生成可选版本
+    assert!(!code.contains("PartialGetDataRes"));
+
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_partial_variants: true,
+        partial_variant_name: "{name}Patch".to_string(),
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export interface GetDataReqPatch {"));
+  }
+
+  #[test]
+  fn test_format_annotation() {
+    let idl = r#"
+struct Event {
+  // 创建时间
+  1: string created_at (format = "date-time")
+  2: string id (format = "uuid")
+}
+"#;
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(!code.contains("@format"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        format_annotation_keys: vec!["format".to_string()],
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      "  /**\n   * 创建时间\n   *\n   * @format date-time\n   */\n  created_at: string;\n"
+    ));
+    assert!(code.contains("  /** @format uuid */\n  id: string;\n"));
+  }
+
+  #[test]
+  fn test_function_without_args() {
+    let code = generate(
+      r#"
+service ThriftService {
+  GetDataRes Ping()
+  GetDataRes GetData( ) (api.post = "/api/get-data")
+}
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains(
+      "export function Ping(options: _Options): Promise<GetDataRes> {\n  throw new Error('not implemented');\n}\n"
+    ));
+    assert!(code.contains(
+      "export function GetData(options: _Options): Promise<GetDataRes> {\n  const url = `${urlPrefix}/api/get-data`;\n  return fetch(url, { method: 'POST' }, options);\n}\n"
+    ));
+  }
+
+  #[test]
+  fn test_emit_enum_validators() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_enum_validators: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      "\nexport function isItemType(v: unknown): v is ItemType {\n  return v === 0 || v === 1 || v === 2;\n}\n"
+    ));
+
+    let code = generate(
+      "enum E {\n  A = 9223372036854775807\n}",
+      GenerateOptions {
+        emit_enum_validators: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("  return v === 9223372036854775807;\n"));
+  }
+
+  #[test]
+  fn test_doc_wrap() {
+    let idl = r#"
+struct A {
+  // the quick brown fox jumps over the lazy dog and keeps running far away
+  // short line
+  1: string name
+}
+"#;
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(code.contains(
+      "  /**\n   * the quick brown fox jumps over the lazy dog and keeps running far away\n   *\n   * short line\n   */\n"
+    ));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        doc_wrap: Some(30),
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      r#"
+  /**
+   * the quick brown fox jumps
+   * over the lazy dog and
+   * keeps running far away
+   *
+   * short line
+   */
+"#
+    ));
+
+    for line in code.lines() {
+      assert!(line.chars().count() <= 30 || !line.trim_start().starts_with('*'));
+    }
+
+    let code = generate(
+      "// the quick brown fox jumps over the lazy dog\nstruct B {\n  1: string name\n}",
+      GenerateOptions {
+        doc_wrap: Some(30),
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      "/**\n * the quick brown fox jumps\n * over the lazy dog\n */\nexport interface B {"
+    ));
+  }
+
+  #[test]
+  fn test_enum_keyed_map() {
+    let code = generate(
+      r#"
+enum ItemType {
+    Normal = 1
+    Special = 2
+}
+
+struct A {
+    1: map<ItemType, string> names
+    2: optional map<b.B, string> others
+}
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains("  names: Partial<Record<ItemType, string>>;\n"));
+    assert!(code.contains("  others?: Record<b.B, string>;\n"));
+  }
+
+  #[test]
+  fn test_service_output() {
+    let idl = r#"
+service BaseService {
+    Pong Ping()
+}
+
+// 数据服务
+service ThriftService extends BaseService {
+    GetDataResp GetData(1: GetDataReq req)
+}
+"#;
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        service_output: Some(ServiceOutput::AbstractClass),
+        ..Default::default()
+      },
+    );
+
+    assert!(code
+      .contains("export abstract class BaseServiceBase {\n  abstract Ping(): Promise<Pong>;\n}\n"));
+    assert!(code.contains(
+      r#"/** 数据服务 */
+export abstract class ThriftServiceBase extends BaseServiceBase {
+  abstract GetData(req: GetDataReq): Promise<GetDataResp>;
+}
+"#
+    ));
+    assert!(!code.contains("export interface"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        service_output: Some(ServiceOutput::Both),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export interface ThriftService extends BaseService {\n  GetData(req: GetDataReq): Promise<GetDataResp>;\n}\n"));
+    assert!(code.contains(
+      "export abstract class ThriftServiceBase extends BaseServiceBase implements ThriftService {\n"
+    ));
+  }
+
+  #[test]
+  fn test_const_definition() {
+    let code = generate(
+      r#"
+// 最大值
+const i64 BIG = 9223372036854775807
+const list<string> NAMES = ["a", 'it\'s']
+const map<ItemType, i32> WEIGHTS = { ItemType.Normal: 1, ItemType.Special: 2 }
+"#,
+      GenerateOptions {
+        i64_as_string: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("\n/** 最大值 */\nexport const BIG: string = '9223372036854775807';\n"));
+    assert!(code.contains("export const NAMES: Array<string> = ['a', 'it\\'s'];\n"));
+    assert!(code.contains(
+      "export const WEIGHTS: Record<ItemType, number> = { [ItemType.Normal]: 1, [ItemType.Special]: 2 };\n"
+    ));
+  }
+
+  #[test]
+  fn test_map_type() {
+    let field_type = ThriftType::Map(
+      Box::new(ThriftType::String),
+      Box::new(ThriftType::List(Box::new(ThriftType::I32))),
+    );
+
+    assert_eq!(
+      Generator::map_type(&field_type, &GenerateOptions::default()),
+      "Record<string, Array<number>>"
+    );
+    assert_eq!(
+      Generator::map_type(
+        &ThriftType::I64,
+        &GenerateOptions {
+          i64_as_string: true,
+          ..Default::default()
+        }
+      ),
+      "string"
+    );
+  }
+
+  #[test]
+  fn test_union_definition() {
+    let code = generate(
+      r#"
+union Result {
+    0: string data
+    1: i32 code
+    2: optional string msg
+}
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains(
+      "export interface Result {\n  data?: string;\n  code?: number;\n  msg?: string;\n}\n"
+    ));
+  }
+
+  #[test]
+  fn test_json_codecs_use_wire_names() {
+    let code = generate(
+      r#"
+struct Item {
+    1: bool is_ok
+}
+
+struct GetDataRes {
+    1: i32 status (api.body="body_status")
+    2: optional list<Item> item_list
+}
+"#,
+      GenerateOptions {
+        field_naming: FieldNaming::CamelCase,
+        emit_json_codecs: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export interface Item {\n  isOk: boolean;\n}\n"));
+    assert!(code.contains(
+      r#"
+export function encodeItem(value: Item): Record<string, any> {
+  return {
+    is_ok: value.isOk,
+  };
+}
+
+export function decodeItem(json: Record<string, any>): Item {
+  return {
+    isOk: json.is_ok,
+  };
+}
+"#
+    ));
+    assert!(code.contains(
+      "    item_list: value.itemList == null ? value.itemList : value.itemList.map((v) => encodeItem(v)),\n"
+    ));
+    assert!(code.contains("    bodyStatus: json.body_status,\n"));
+  }
+
+  #[test]
+  fn test_camel_case_fetch_uses_codecs() {
+    let idl = r#"
+struct GetDataReq {
+    1: string item_id
+}
+
+struct GetDataRes {
+    1: bool is_ok
+}
+
+service ThriftService {
+    GetDataRes GetData(1: GetDataReq req) (api.post = "/api/get-data")
+    GetDataRes Query(1: optional GetDataReq req) (api.get = "/api/query")
+}
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        field_naming: FieldNaming::CamelCase,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export function encodeGetDataReq(value: GetDataReq)"));
+    assert!(code.contains("  const body = JSON.stringify(encodeGetDataReq(req));\n"));
+    assert!(code.contains(
+      "  return fetch(url, { method: 'POST', body }, options).then((res) => decodeGetDataRes(res));\n"
+    ));
+    assert!(code.contains(
+      "  const query = new URLSearchParams(req == null ? req : encodeGetDataReq(req)).toString();\n"
+    ));
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(code.contains("  const body = JSON.stringify(req);\n"));
+    assert!(code.contains("  return fetch(url, { method: 'POST', body }, options);\n"));
+    assert!(!code.contains("encodeGetDataReq"));
+  }
+
+  #[test]
+  fn test_key_pattern_annotation() {
+    let idl = r#"
+struct Cache {
+    1: map<string, i32> users (key.pattern = "user:*")
+    2: map<i32, i32> ids (key.pattern = "id:*")
+}
+"#;
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        key_pattern_annotation: Some("key.pattern".to_string()),
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("  users: Record<`user:${string}`, number>;\n"));
+    assert!(code.contains("  ids: Record<number, number>;\n"));
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(code.contains("  users: Record<string, number>;\n"));
+  }
+
+  #[test]
+  fn test_mixed_leading_comments() {
+    let mut document = Parser::new(
+      r#"
+struct GetDataReq {
+    // 这是单行注释
+    // 这也是单行注释
+    /* 这是多行注释
+       第二行 */
+    1: string parameters
+}
+"#,
+    )
+    .parse()
+    .unwrap();
+
+    let TopDefinition::Struct(struct_definition) = &document.body[0] else {
+      panic!("expected struct");
+    };
+    let comments = &struct_definition.fields[0].comments;
+    assert_eq!(comments.len(), 3);
+    assert_eq!(comments[0].line_value(), "这是单行注释");
+    assert_eq!(comments[1].line_value(), "这也是单行注释");
+    assert_eq!(comments[2].block_value(), ["这是多行注释", "第二行"]);
+
+    let code = Generator::new(&mut document).build(GenerateOptions::default());
+    assert!(code.contains(
+      r#"  /**
+   * 这是单行注释
+   *
+   * 这也是单行注释
+   *
+   * 这是多行注释
+   * 第二行
+   */
+  parameters: string;
+"#
+    ));
+  }
+
+  #[test]
+  fn test_default_export() {
+    let options = GenerateOptions {
+      default_export: true,
+      ..Default::default()
+    };
+
+    let code = generate("struct A {\n  1: i32 id\n}", options.clone());
+    assert!(code.ends_with("export interface A {\n  id: number;\n}\n\nexport default A;\n"));
+
+    let code = generate(
+      "struct A {\n  1: i32 id\n}\nservice S {\n  A GetA()\n}",
+      options.clone(),
+    );
+    assert!(code.ends_with("\nexport default A;\n"));
+
+    let code = generate("struct A {\n  1: i32 id\n}\nenum E {\n  X = 1\n}", options);
+    assert!(!code.contains("export default"));
+  }
+
+  #[test]
+  fn test_max_line_length() {
+    let idl = r#"
+struct A {
+    1: optional map<string, list<SomeVeryLongTypeName>> some_long_field_name
+    2: optional i32 id
+}
+"#;
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        optional_null: true,
+        max_line_length: Some(60),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"  some_long_field_name?:
+    | Record<string, Array<SomeVeryLongTypeName>>
+    | null;
+  id?: number | null;
+"#
+    ));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        optional_null: true,
+        ..Default::default()
+      },
+    );
+    assert!(code
+      .contains("  some_long_field_name?: Record<string, Array<SomeVeryLongTypeName>> | null;\n"));
+
+    // 没有联合类型时拆分泛型参数，仍然过长的参数继续向内拆分
+    let code = generate(
+      r#"
+struct B {
+    1: map<string, list<map<string, list<i32>>>> mmmmmmmmmm
+    2: list<i32> ids
+}
+typedef map<string, list<map<string, list<i32>>>> LongMap
+"#,
+      GenerateOptions {
+        max_line_length: Some(36),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"  mmmmmmmmmm: Record<
+    string,
+    Array<
+      Record<string, Array<number>>
+    >
+  >;
+  ids: Array<number>;
+"#
+    ));
+    assert!(code.contains(
+      r#"export type LongMap = Record<
+  string,
+  Array<
+    Record<string, Array<number>>
+  >
+>;
+"#
+    ));
+  }
+
+  #[test]
+  fn test_zod_validation_target() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        validation_target: Some(ValidationTarget::Zod),
+        ..Default::default()
+      },
+    );
+
+    assert!(code
+      .contains("// This file is auto generated by thrift_parser.\n\nimport { z } from 'zod';\n"));
+    assert!(code.contains(
+      r#"
+export const GetDataReqSchema = z.object({
+  parameters: z.string(),
+  query_status: z.number().int(),
+  money: z.number(),
+  is_ok: z.boolean(),
+  kvs: z.record(z.string(), z.string()).optional(),
+  a_list: z.array(z.lazy(() => a.ASchema)),
+  item_type: z.lazy(() => ItemTypeSchema),
+});
+"#
+    ));
+    assert!(code.contains("\nexport const ItemTypeSchema = z.nativeEnum(ItemType);\n"));
+  }
+
+  #[test]
+  fn test_union_discriminator() {
+    let code = generate(
+      r#"
+union Result {
+    0: string data
+    1: i32 code
+} (ts.discriminator = "type")
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert!(code.contains(
+      r#"
+export type Result =
+  | { type: 'data'; data: string }
+  | { type: 'code'; code: number };
+"#
+    ));
+  }
+
+  #[test]
+  fn test_union_discriminator_codecs() {
+    let idl = r#"
+struct Item {
+    1: bool is_ok
+}
+
+union Result {
+    0: Item item_data
+    1: i32 code
+} (ts.discriminator = "type")
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        field_naming: FieldNaming::CamelCase,
+        emit_json_codecs: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export function encodeResult(value: Result): Record<string, any> {
+  switch (value.type) {
+    case 'itemData':
+      return { item_data: encodeItem(value.itemData) };
+    case 'code':
+      return { code: value.code };
+  }
+}
+
+export function decodeResult(json: Record<string, any>): Result {
+  if (json.item_data != null) {
+    return { type: 'itemData', itemData: decodeItem(json.item_data) };
+  }
+  if (json.code != null) {
+    return { type: 'code', code: json.code };
+  }
+  throw new Error('Invalid Result: none of the members is set');
+}
+"#
+    ));
+
+    // 判别字段不是合法的标识符时使用 `value['a-b']` 访问
+    let code = generate(
+      r#"
+union Result {
+    0: string data
+} (ts.discriminator = "a-b")
+"#,
+      GenerateOptions {
+        emit_json_codecs: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("  switch (value['a-b']) {\n"));
+    assert!(code.contains("    return { 'a-b': 'data', data: json.data };\n"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        validation_target: Some(ValidationTarget::Zod),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export const ResultSchema = z.discriminatedUnion('type', [
+  z.object({ type: z.literal('item_data'), item_data: z.lazy(() => ItemSchema) }),
+  z.object({ type: z.literal('code'), code: z.number().int() }),
+]);
+"#
+    ));
+  }
+
+  #[test]
+  fn test_generate_large_document() {
+    let mut idl = "struct Big {\n".to_string();
+    for i in 1..=5000 {
+      idl.push_str(&format!("  {}: list<list<i64>> field_{}\n", i, i));
+    }
+    idl.push_str("}\nservice BigService {\n");
+    for i in 0..2000 {
+      idl.push_str(&format!("  Big Method{}(1: Big req)\n", i));
+    }
+    idl.push_str("}\n");
+
+    let code = generate(&idl, GenerateOptions::default());
+    assert!(code.contains("  field_5000: Array<Array<"));
+    assert!(code.contains("Method1999"));
+  }
+
+  #[test]
+  fn test_generate_max_type_depth() {
+    let nested = |depth: usize| {
+      format!(
+        "struct Item {{\n  1: i32 id\n}}\nstruct Deep {{\n  1: {}map<string, Item>{} items\n}}",
+        "list<".repeat(depth - 1),
+        ">".repeat(depth - 1)
+      )
+    };
+    let code = generate(
+      &nested(MAX_TYPE_DEPTH),
+      GenerateOptions {
+        emit_json_codecs: true,
+        emit_struct_guards: true,
+        validation_target: Some(ValidationTarget::Zod),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(&format!(
+      "  items: {}Record<string, Item>{};\n",
+      "Array<".repeat(MAX_TYPE_DEPTH - 1),
+      ">".repeat(MAX_TYPE_DEPTH - 1)
+    )));
+    assert!(code.contains("encodeItem(v)"));
+    assert!(Parser::new(&nested(MAX_TYPE_DEPTH + 1)).parse().is_err());
+  }
+
+  #[test]
+  fn test_generated_marker() {
+    let idl = "struct A {\n  1: i32 id\n}";
+    let code = generate(
+      idl,
+      GenerateOptions {
+        generated_marker: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("// This file is auto generated by thrift_parser.\n// @generated\n\n"));
+    assert!(!generate(idl, GenerateOptions::default()).contains("@generated"));
+  }
+
+  #[test]
+  fn test_for_target() {
+    let options = GenerateOptions::for_target(Target::NodeEsm);
+    assert_eq!(options.import_extension.as_deref(), Some(".js"));
+    assert_eq!(options.module_kind, ModuleKind::Esm);
+
+    let idl = "include \"a.thrift\"\nstruct B {\n  1: a.A a\n}";
+    assert!(generate(idl, options).contains("import * as a from './a.js';\n"));
+    assert!(generate(idl, GenerateOptions::for_target(Target::NodeCjs))
+      .contains("import a = require('./a');\n"));
+    assert!(generate(idl, GenerateOptions::for_target(Target::DenoLand))
+      .contains("import * as a from './a.ts';\n"));
+  }
+
+  #[test]
+  fn test_import_order() {
+    let code = generate(
+      "include \"b.thrift\"\ninclude \"a.thrift\"",
+      GenerateOptions::default(),
+    );
+    assert!(code.contains("import * as b from './b';\nimport * as a from './a';\n"));
+  }
+
+  #[test]
+  fn test_field_ts_signature() {
+    let document = Parser::new(IDL).parse().unwrap();
+    let fields = match &document.body[2] {
+      TopDefinition::Struct(struct_definition) => &struct_definition.fields,
+      _ => panic!(),
+    };
+
+    assert_eq!(
+      fields[4].ts_signature(&GenerateOptions::default(), &document),
+      "kvs?: Record<a.A, string>"
+    );
+    assert_eq!(
+      fields[4].ts_signature(
+        &GenerateOptions {
+          optional_null: true,
+          ..Default::default()
+        },
+        &document
+      ),
+      "kvs?: Record<a.A, string> | null"
+    );
+    assert_eq!(
+      fields[1].ts_signature(&GenerateOptions::default(), &document),
+      "query_status: number"
+    );
+    assert_eq!(
+      fields[6].ts_signature(&GenerateOptions::default(), &document),
+      "item_type: ItemType"
+    );
+  }
+
+  #[test]
+  fn test_result_decoder() {
+    let code = generate(
+      r#"exception NotFound {
+  1: string message
+}
+struct Data {
+  1: string value
+}
+service S {
+  Data GetData(1: i32 id) throws (1: NotFound not_found 2: string reason)
+}"#,
+      GenerateOptions {
+        emit_json_codecs: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export interface NotFound {\n  message: string;\n}\n"));
+    assert!(code.contains(
+      r#"
+export interface GetDataResult {
+  success?: Data;
+  not_found?: NotFound;
+  reason?: string;
+}
+
+export function decodeGetDataResult(json: Record<string, any>): GetDataResult {
+  return {
+    success: json['0'] == null ? json['0'] : decodeData(json['0']),
+    not_found: json['1'] == null ? json['1'] : decodeNotFound(json['1']),
+    reason: json['2'],
+  };
+}
+"#
+    ));
+    assert!(!generate(
+      "service S {\n  void Ping() throws (1: string reason)\n}",
+      GenerateOptions::default()
+    )
+    .contains("PingResult"));
+  }
+
+  #[test]
+  fn test_const_readonly() {
+    let idl = r#"
+const i32 MAX = 10
+const list<string> NAMES = ["a", "b"]
+const map<string, i32> WEIGHTS = { "a": 1 }
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        const_as_const: true,
+        const_readonly: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export const MAX = 10 as const;\n"));
+    assert!(code.contains("export const NAMES: readonly [string, string] = ['a', 'b'] as const;\n"));
+    assert!(code
+      .contains("export const WEIGHTS: Readonly<Record<string, number>> = { 'a': 1 } as const;\n"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        const_as_const: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export const NAMES = ['a', 'b'] as const;\n"));
+
+    let code = generate(
+      r#"
+enum ItemType {
+  Normal = 1
+}
+const i32 A = 1
+const i32 B = A
+const ItemType C = ItemType.Normal
+"#,
+      GenerateOptions {
+        const_as_const: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export const A = 1 as const;\n"));
+    assert!(code.contains("export const B: number = A;\n"));
+    assert!(code.contains("export const C: ItemType = ItemType.Normal;\n"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        const_readonly: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export const MAX: number = 10;\n"));
+    assert!(code.contains("export const NAMES: ReadonlyArray<string> = ['a', 'b'];\n"));
+  }
+
+  #[test]
+  fn test_emit_method_map() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_method_map: true,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      "\nexport type ThriftServiceMethods = {\n  getData: { args: GetDataReq; result: GetDataRes };\n"
+    ));
+
+    let code = generate(
+      "service Base {\n  void Ping()\n}\nservice S extends Base {\n  i32 Add(1: i32 a 2: optional i32 b)\n}",
+      GenerateOptions {
+        emit_method_map: true,
+        ..Default::default()
+      },
+    );
+    assert!(
+      code.contains("export type BaseMethods = {\n  ping: { args: void; result: void };\n};\n")
+    );
+    assert!(code.contains(
+      "export type SMethods = BaseMethods & {\n  add: { args: { a: number; b?: number }; result: number };\n};\n"
+    ));
+    assert!(!generate(IDL, GenerateOptions::default()).contains("Methods"));
+  }
+
+  #[test]
+  fn test_enum_object_suffix() {
+    let idl = r#"
+enum ItemType {
+    // 未知
+    Unknown = 0
+    Normal
+}
+struct Item {
+    1: ItemType item_type
+}
+const ItemType DEFAULT_TYPE = ItemType.Normal
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        enum_style: EnumStyle::ConstObject,
+        enum_object_suffix: Some("Enum".to_string()),
+        emit_enum_match: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export const ItemTypeEnum = {
+  /** 未知 */
+  Unknown: 0,
+  Normal: 1,
+} as const;
+export type ItemType = (typeof ItemTypeEnum)[keyof typeof ItemTypeEnum];
+"#
+    ));
+    assert!(code.contains("  item_type: ItemType;\n"));
+    assert!(code.contains("export const DEFAULT_TYPE: ItemType = ItemTypeEnum.Normal;\n"));
+    assert!(code.contains("    case ItemTypeEnum.Normal:\n"));
+
+    let code = generate(
+      idl,
+      GenerateOptions {
+        enum_style: EnumStyle::ConstObject,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains("export const ItemType = {\n"));
+    assert!(code.contains("export const DEFAULT_TYPE: ItemType = ItemType.Normal;\n"));
+  }
+
+  #[test]
+  fn test_senum_definition() {
+    let code = generate(
+      "// 颜色\nsenum Foo {\n  \"a\"\n  \"b\",\n  'c';\n}",
+      GenerateOptions::default(),
+    );
+    assert!(code.contains("\n/** 颜色 */\nexport type Foo = 'a' | 'b' | 'c';\n"));
+  }
+
+  #[test]
+  fn test_emit_struct_guards() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        emit_struct_guards: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export function isGetDataReq(v: unknown): v is GetDataReq {
+  if (typeof v !== 'object' || v === null) {
+    return false;
+  }
+  const o = v as Record<string, unknown>;
+  return (
+    typeof o.parameters === 'string' &&
+    typeof o.query_status === 'number' &&
+    typeof o.money === 'number' &&
+    typeof o.is_ok === 'boolean' &&
+    Array.isArray(o.a_list) &&
+    typeof o.item_type === 'number'
+  );
+}
+"#
+    ));
+  }
+
+  #[test]
+  fn test_header() {
+    let mut document = Parser::new("struct A {\n  1: i32 id\n}").parse().unwrap();
+    let code = Generator::new(&mut document)
+      .source_file("sub/a.thrift")
+      .build(GenerateOptions {
+        header: Some("// Generated from {file} by v{version}\n// {date}\n".to_string()),
+        ..Default::default()
+      });
+
+    let header = format!(
+      "// This file is auto generated by thrift_parser.\n// Generated from sub/a.thrift by v{}\n// ",
+      env!("CARGO_PKG_VERSION")
+    );
+    assert!(code.contains(&header));
+
+    let date = code[code.find(&header).unwrap() + header.len()..]
+      .lines()
+      .next()
+      .unwrap();
+    assert_eq!(date.len(), 10);
+    assert!(date.starts_with("20"));
+  }
+
+  #[test]
+  fn test_member_order() {
+    let code = generate(
+      "enum ItemType {\n  Unknown = 0\n  Normal\n  Special\n}\nunion U {\n  1: i32 b\n  2: i32 a\n}",
+      GenerateOptions {
+        member_order: MemberOrder::Alphabetical,
+        ..Default::default()
+      },
+    );
+
+    assert!(
+      code.contains("export enum ItemType {\n  Normal = 1,\n  Special = 2,\n  Unknown = 0,\n}\n")
+    );
+    assert!(code.contains("export interface U {\n  a?: number;\n  b?: number;\n}\n"));
+  }
+
+  #[test]
+  fn test_struct_extends_annotation() {
+    let code = generate(
+      r#"
+include "base.thrift"
+
+struct Local {
+  1: i32 id
+}
+
+struct A {
+  1: string name
+} (extends = "Local")
+
+struct B {
+  1: string name
+} (extends = "base.Base")
+"#,
+      GenerateOptions {
+        struct_extends_annotation: Some("extends".to_string()),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export interface Local {\n"));
+    assert!(code.contains("export interface A extends Local {\n  name: string;\n}\n"));
+    assert!(code.contains("export interface B extends base.Base {\n  name: string;\n}\n"));
+
+    let code = generate(
+      "struct A {\n  1: string name\n} (extends = \"Local\")",
+      GenerateOptions::default(),
+    );
+    assert!(code.contains("export interface A {\n"));
+  }
+
+  #[test]
+  fn test_const_enum() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        enum_style: EnumStyle::ConstEnum,
+        validation_target: Some(ValidationTarget::Zod),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export const enum ItemType {
+  /** 未知 */
+  Unknown = 0,
+  /** 普通 */
+  Normal = 1,
+  /** 特别 */
+  Special = 2,
+}
+"#
+    ));
+    assert!(code.contains(
+      "export const ItemTypeSchema = z.number().refine((v): v is ItemType => [0, 1, 2].includes(v));\n"
+    ));
+  }
+
+  #[test]
+  fn test_post_process() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        post_process: Some(PostProcess::new(|code| {
+          code.replace("// @ts-nocheck", "// @TS-NOCHECK")
+        })),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.starts_with("/* eslint-disable */\n// @TS-NOCHECK\n"));
+    assert!(!code.contains("// @ts-nocheck"));
+  }
+
+  #[test]
+  fn test_const_group_annotation() {
+    let idl = r#"
+const i32 RED = 1 (enum_group = "Color")
+const i32 GREEN = 2 (enum_group = "Color")
+const string NAME = "n"
+const i32 BLUE = 4 (enum_group = "Color")
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        const_group_annotation: Some("enum_group".to_string()),
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains("export const RED: number = 1;\n"));
+    assert!(code.ends_with(
+      "export const BLUE: number = 4;\n\nexport enum Color {\n  RED = 1,\n  GREEN = 2,\n  BLUE = 4,\n}\n"
+    ));
+    assert_eq!(code.matches("export enum Color").count(), 1);
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(!code.contains("export enum Color"));
+  }
+
+  #[test]
+  fn test_enum_member_comments() {
+    let code = generate(IDL, GenerateOptions::default());
+
+    assert!(code.contains(
+      "export enum ItemType {\n  /** 未知 */\n  Unknown = 0,\n  /** 普通 */\n  Normal = 1,\n  /** 特别 */\n  Special = 2,\n}\n"
+    ));
+
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        enum_style: EnumStyle::ConstObject,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      "export const ItemType = {\n  /** 未知 */\n  Unknown: 0,\n  /** 普通 */\n  Normal: 1,\n  /** 特别 */\n  Special: 2,\n} as const;\n"
+    ));
+  }
+
+  #[test]
+  fn test_string_enum() {
+    let code = generate(
+      IDL,
+      GenerateOptions {
+        enum_style: EnumStyle::StringEnum,
+        emit_enum_validators: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.contains(
+      r#"
+export enum ItemType {
+  /** 未知 */
+  Unknown = 'Unknown',
+  /** 普通 */
+  Normal = 'Normal',
+  /** 特别 */
+  Special = 'Special',
+}
+"#
+    ));
+    assert!(code.contains("return v === 'Unknown' || v === 'Normal' || v === 'Special';\n"));
+  }
+
+  #[test]
+  fn test_emit_events_map() {
+    let idl = r#"
+service ThriftService {
+    GetDataRes GetData(1: GetDataReq req)
+    oneway void ReportEvent(1: Event event)
+}
+"#;
+    let code = generate(
+      idl,
+      GenerateOptions {
+        emit_events_map: true,
+        ..Default::default()
+      },
+    );
+
+    assert!(code.ends_with("\nexport type ThriftServiceEvents = {\n  reportEvent: Event;\n};\n"));
+
+    let code = generate(idl, GenerateOptions::default());
+    assert!(!code.contains("ThriftServiceEvents"));
   }
 }
@@ -0,0 +1,459 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+  generate::{field_format, field_name, EnumStyle, GenerateOptions},
+  parse::{
+    EnumDefinition, FieldDefinition, IncludeDefinition, Requiredness, SenumDefinition,
+    StructDefinition, ThriftDocument, ThriftType, TypedefDefinition, UnionDefinition,
+  },
+  visit::Visit,
+};
+
+const INDENT: &str = "  ";
+
+enum JsonValue {
+  String(String),
+  Number(String),
+  Array(Vec<JsonValue>),
+  Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+  fn string(value: &str) -> Self {
+    JsonValue::String(value.to_string())
+  }
+
+  fn object(entries: Vec<(&str, JsonValue)>) -> Self {
+    JsonValue::Object(
+      entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect(),
+    )
+  }
+
+  fn format(&self, indent: &str) -> String {
+    match self {
+      JsonValue::String(value) => format_json_string(value),
+      JsonValue::Number(value) => value.clone(),
+      JsonValue::Array(items) => {
+        if items.is_empty() {
+          return "[]".to_string();
+        }
+
+        let items = items
+          .iter()
+          .map(|item| item.format(indent))
+          .collect::<Vec<_>>();
+
+        format!("[{}]", items.join(", "))
+      }
+      JsonValue::Object(entries) => {
+        if entries.is_empty() {
+          return "{}".to_string();
+        }
+
+        let inner_indent = indent.to_string() + INDENT;
+        let entries = entries
+          .iter()
+          .map(|(key, value)| {
+            format!(
+              "{}{}: {}",
+              inner_indent,
+              format_json_string(key),
+              value.format(&inner_indent)
+            )
+          })
+          .collect::<Vec<_>>();
+
+        format!("{{\n{}\n{}}}", entries.join(",\n"), indent)
+      }
+    }
+  }
+}
+
+fn format_json_string(value: &str) -> String {
+  let mut code = "\"".to_string();
+
+  for c in value.chars() {
+    match c {
+      '"' => code.push_str("\\\""),
+      '\\' => code.push_str("\\\\"),
+      '\n' => code.push_str("\\n"),
+      '\r' => code.push_str("\\r"),
+      '\t' => code.push_str("\\t"),
+      c if (c as u32) < 0x20 => code.push_str(&format!("\\u{:04x}", c as u32)),
+      c => code.push(c),
+    }
+  }
+
+  code.push('"');
+  code
+}
+
+/// 根据 thrift 文档生成 JSON Schema，struct、enum、typedef 都会放到 `definitions` 中
+pub struct JsonSchemaGenerator<'a> {
+  document: &'a mut ThriftDocument,
+  include_paths: HashMap<String, String>,
+}
+
+impl<'a> JsonSchemaGenerator<'a> {
+  pub fn new(document: &'a mut ThriftDocument) -> Self {
+    Self {
+      document,
+      include_paths: HashMap::new(),
+    }
+  }
+
+  /// 指定 include 对应的 schema 地址，key 为 include 的路径，未指定时使用 `./{文件名}.json`
+  pub fn include_paths(mut self, include_paths: HashMap<String, String>) -> Self {
+    self.include_paths = include_paths;
+    self
+  }
+
+  pub fn build(&mut self, options: GenerateOptions) -> String {
+    let mut visitor = Visitor::new(options);
+    visitor.include_paths = self.include_paths.clone();
+    visitor.visit_document(self.document);
+
+    let schema = JsonValue::object(vec![
+      (
+        "$schema",
+        JsonValue::string("http://json-schema.org/draft-07/schema#"),
+      ),
+      ("definitions", JsonValue::Object(visitor.definitions)),
+    ]);
+
+    schema.format("") + "\n"
+  }
+}
+
+struct Visitor {
+  definitions: Vec<(String, JsonValue)>,
+  options: GenerateOptions,
+  include_paths: HashMap<String, String>,
+  /// include 的文件名到 schema 地址的映射，用于解析 `a.A` 这样的引用
+  include_uris: HashMap<String, String>,
+}
+
+impl Visitor {
+  pub fn new(options: GenerateOptions) -> Self {
+    Self {
+      definitions: vec![],
+      options,
+      include_paths: HashMap::new(),
+      include_uris: HashMap::new(),
+    }
+  }
+
+  fn format_thrift_type(&self, thrift_type: &ThriftType) -> JsonValue {
+    match thrift_type {
+      ThriftType::Void => JsonValue::object(vec![("type", JsonValue::string("null"))]),
+      ThriftType::String => JsonValue::object(vec![("type", JsonValue::string("string"))]),
+      ThriftType::I16 | ThriftType::I32 => {
+        JsonValue::object(vec![("type", JsonValue::string("integer"))])
+      }
+      ThriftType::I64 => {
+        if self.options.i64_as_string {
+          JsonValue::object(vec![("type", JsonValue::string("string"))])
+        } else {
+          JsonValue::object(vec![("type", JsonValue::string("integer"))])
+        }
+      }
+      ThriftType::Double => JsonValue::object(vec![("type", JsonValue::string("number"))]),
+      ThriftType::Bool => JsonValue::object(vec![("type", JsonValue::string("boolean"))]),
+      ThriftType::List(ty) => JsonValue::object(vec![
+        ("type", JsonValue::string("array")),
+        ("items", self.format_thrift_type(ty)),
+      ]),
+      // JSON 对象的 key 只能是字符串，所以只约束 value
+      ThriftType::Map(_, value_ty) => JsonValue::object(vec![
+        ("type", JsonValue::string("object")),
+        ("additionalProperties", self.format_thrift_type(value_ty)),
+      ]),
+      ThriftType::Identifier(id) => {
+        if let Some((scope, name)) = id.value.rsplit_once('.') {
+          match self.include_uris.get(scope) {
+            Some(uri) => JsonValue::object(vec![(
+              "$ref",
+              JsonValue::String(format!("{}#/definitions/{}", uri, name)),
+            )]),
+            // 引用了未知文件中的类型，不做约束
+            None => JsonValue::object(vec![]),
+          }
+        } else {
+          JsonValue::object(vec![(
+            "$ref",
+            JsonValue::String(format!("#/definitions/{}", id.value)),
+          )])
+        }
+      }
+    }
+  }
+
+  fn format_properties(&self, fields: &[FieldDefinition]) -> JsonValue {
+    let mut properties = vec![];
+
+    for field in fields {
+      let mut property = self.format_thrift_type(&field.field_type);
+
+      if let (JsonValue::Object(entries), Some(format)) =
+        (&mut property, field_format(field, &self.options))
+      {
+        entries.push(("format".to_string(), JsonValue::String(format)));
+      }
+
+      properties.push((field_name(field), property));
+    }
+
+    JsonValue::Object(properties)
+  }
+}
+
+impl Visit for Visitor {
+  fn visit_include_definition(&mut self, include_definition: &mut IncludeDefinition) {
+    let path = &include_definition.path.value;
+    let Some(stem) = Path::new(path).file_stem().and_then(|stem| stem.to_str()) else {
+      return;
+    };
+    let uri = self
+      .include_paths
+      .get(path)
+      .cloned()
+      .unwrap_or(format!("./{}.json", stem));
+
+    self.include_uris.insert(stem.to_string(), uri);
+  }
+
+  fn visit_struct_definition(&mut self, struct_definition: &mut StructDefinition) {
+    let required = struct_definition
+      .fields
+      .iter()
+      .filter(|field| !matches!(field.requiredness, Some(Requiredness::Optional)))
+      .map(|field| JsonValue::String(field_name(field)))
+      .collect();
+
+    self.definitions.push((
+      struct_definition.name.value.clone(),
+      JsonValue::object(vec![
+        ("type", JsonValue::string("object")),
+        (
+          "properties",
+          self.format_properties(&struct_definition.fields),
+        ),
+        ("required", JsonValue::Array(required)),
+      ]),
+    ));
+  }
+
+  fn visit_union_definition(&mut self, union_definition: &mut UnionDefinition) {
+    // union 最多只有一个字段有值
+    self.definitions.push((
+      union_definition.name.value.clone(),
+      JsonValue::object(vec![
+        ("type", JsonValue::string("object")),
+        (
+          "properties",
+          self.format_properties(&union_definition.fields),
+        ),
+        ("maxProperties", JsonValue::Number("1".to_string())),
+      ]),
+    ));
+  }
+
+  fn visit_enum_definition(&mut self, enum_definition: &mut EnumDefinition) {
+    // StringEnum 在 JSON 中使用成员名称作为取值
+    let (value_type, values) = if self.options.enum_style == EnumStyle::StringEnum {
+      let values = enum_definition
+        .members
+        .iter()
+        .map(|member| JsonValue::string(&member.name.value))
+        .collect();
+
+      ("string", values)
+    } else {
+      let values = enum_definition
+        .member_values()
+        .into_iter()
+        .map(|value| JsonValue::Number(value.to_string()))
+        .collect();
+
+      ("integer", values)
+    };
+
+    self.definitions.push((
+      enum_definition.name.value.clone(),
+      JsonValue::object(vec![
+        ("type", JsonValue::string(value_type)),
+        ("enum", JsonValue::Array(values)),
+      ]),
+    ));
+  }
+
+  fn visit_senum_definition(&mut self, senum_definition: &mut SenumDefinition) {
+    let values = senum_definition
+      .values
+      .iter()
+      .map(|value| JsonValue::string(&value.value))
+      .collect();
+
+    self.definitions.push((
+      senum_definition.name.value.clone(),
+      JsonValue::object(vec![
+        ("type", JsonValue::string("string")),
+        ("enum", JsonValue::Array(values)),
+      ]),
+    ));
+  }
+
+  fn visit_typedef_definition(&mut self, typedef_definition: &mut TypedefDefinition) {
+    self.definitions.push((
+      typedef_definition.name.value.clone(),
+      self.format_thrift_type(&typedef_definition.field_type),
+    ));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse::Parser;
+
+  fn generate(idl: &str, options: GenerateOptions) -> String {
+    let mut document = Parser::new(idl).parse().unwrap();
+    JsonSchemaGenerator::new(&mut document).build(options)
+  }
+
+  #[test]
+  fn test_struct_schema() {
+    let schema = generate(
+      r#"
+struct GetDataReq {
+    1: string parameters
+    2: i32 status (api.query="query_status")
+    3: double money
+    3: bool is_ok
+    2: optional map<a.A, string> kvs
+    3: required list<a.A> a_list
+    6: ItemType item_type
+}
+
+enum ItemType {
+    Unknown = 0
+    Normal = 1
+    Special = 2
+}
+"#,
+      GenerateOptions::default(),
+    );
+
+    assert_eq!(
+      schema,
+      r##"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "definitions": {
+    "GetDataReq": {
+      "type": "object",
+      "properties": {
+        "parameters": {
+          "type": "string"
+        },
+        "query_status": {
+          "type": "integer"
+        },
+        "money": {
+          "type": "number"
+        },
+        "is_ok": {
+          "type": "boolean"
+        },
+        "kvs": {
+          "type": "object",
+          "additionalProperties": {
+            "type": "string"
+          }
+        },
+        "a_list": {
+          "type": "array",
+          "items": {}
+        },
+        "item_type": {
+          "$ref": "#/definitions/ItemType"
+        }
+      },
+      "required": ["parameters", "query_status", "money", "is_ok", "a_list", "item_type"]
+    },
+    "ItemType": {
+      "type": "integer",
+      "enum": [0, 1, 2]
+    }
+  }
+}
+"##
+    );
+  }
+
+  #[test]
+  fn test_string_enum_schema() {
+    let schema = generate(
+      "enum ItemType {\n  Unknown = 0\n  Normal = 1\n}",
+      GenerateOptions {
+        enum_style: EnumStyle::StringEnum,
+        ..Default::default()
+      },
+    );
+    assert!(schema.contains(
+      "\"ItemType\": {\n      \"type\": \"string\",\n      \"enum\": [\"Unknown\", \"Normal\"]\n    }"
+    ));
+  }
+
+  #[test]
+  fn test_typedef_schema() {
+    let schema = generate("typedef list<i64> Ids", GenerateOptions::default());
+    assert!(schema.contains("\"Ids\": {\n      \"type\": \"array\""));
+  }
+
+  #[test]
+  fn test_format_keyword() {
+    let schema = generate(
+      r#"struct Event { 1: string created_at (format = "date-time") }"#,
+      GenerateOptions {
+        format_annotation_keys: vec!["format".to_string()],
+        ..Default::default()
+      },
+    );
+    assert!(schema.contains(
+      "\"created_at\": {\n          \"type\": \"string\",\n          \"format\": \"date-time\"\n        }"
+    ));
+  }
+
+  #[test]
+  fn test_union_schema() {
+    let schema = generate(
+      "union Result {\n  0: string data\n  1: i32 code\n}",
+      GenerateOptions::default(),
+    );
+    assert!(schema.contains("      },\n      \"maxProperties\": 1\n    }"));
+    assert!(!schema.contains("\"required\""));
+  }
+
+  #[test]
+  fn test_include_ref() {
+    let idl = "include \"common/base.thrift\"\nstruct A {\n  1: base.Base base\n  2: other.B b\n}";
+    let schema = generate(idl, GenerateOptions::default());
+    assert!(schema
+      .contains("\"base\": {\n          \"$ref\": \"./base.json#/definitions/Base\"\n        }"));
+    assert!(schema.contains("\"b\": {}"));
+
+    let mut document = Parser::new(idl).parse().unwrap();
+    let schema = JsonSchemaGenerator::new(&mut document)
+      .include_paths(HashMap::from([(
+        "common/base.thrift".to_string(),
+        "https://example.com/schemas/base.json".to_string(),
+      )]))
+      .build(GenerateOptions::default());
+    assert!(
+      schema.contains("\"$ref\": \"https://example.com/schemas/base.json#/definitions/Base\"")
+    );
+  }
+}
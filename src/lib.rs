@@ -1,12 +1,16 @@
 pub mod compile;
+pub mod diff;
 pub mod generate;
+pub mod json_schema;
 pub mod parse;
+pub mod token;
+pub mod validate;
 pub mod visit;
 
 #[cfg(test)]
 mod tests {
   use crate::{
-    compile::Compiler,
+    compile::{CompileError, Compiler},
     generate::{GenerateOptions, Generator},
     parse::Parser,
   };
@@ -70,4 +74,301 @@ service ThriftService {
     .compile()
     .unwrap();
   }
+
+  #[test]
+  fn test_compiler_include_namespace_alias() {
+    Compiler::new(
+      vec!["main.thrift".to_string()],
+      "./tests/fixtures/include_alias".to_string(),
+      "./tests/fixtures/include_alias/out".to_string(),
+      GenerateOptions::default(),
+    )
+    .compile()
+    .unwrap();
+
+    let code = std::fs::read_to_string("./tests/fixtures/include_alias/out/main.ts").unwrap();
+    assert!(code.contains("import * as a from './types';\n"));
+    assert!(code.contains("  item: a.A;\n"));
+    assert!(std::path::Path::new("./tests/fixtures/include_alias/out/types.ts").exists());
+  }
+
+  #[test]
+  fn test_compiler_overlapping_dirs() {
+    for out_dir in [
+      "./tests/fixtures/compiler",
+      "./tests/fixtures/compiler/",
+      "./tests/fixtures",
+      "./tests/fixtures/compiler/../compiler",
+    ] {
+      let ret = Compiler::new(
+        vec!["service.thrift".to_string()],
+        "./tests/fixtures/compiler".to_string(),
+        out_dir.to_string(),
+        GenerateOptions::default(),
+      )
+      .compile();
+
+      assert!(ret
+        .unwrap_err()
+        .to_string()
+        .contains("is the same as or contains src_dir"));
+    }
+
+    assert!(std::path::Path::new("./tests/fixtures/compiler/service.thrift").exists());
+  }
+
+  #[test]
+  fn test_compiler_bundle() {
+    Compiler::new(
+      vec!["service.thrift".to_string()],
+      "./tests/fixtures/compiler".to_string(),
+      "./tests/fixtures/bundle".to_string(),
+      GenerateOptions::default(),
+    )
+    .bundle("types.ts")
+    .unwrap();
+
+    let code = std::fs::read_to_string("./tests/fixtures/bundle/types.ts").unwrap();
+    let positions = [
+      "export interface B {",
+      "export enum A {",
+      "export enum ItemType {",
+      "export interface GetDataReq {",
+      "export interface GetDataRes {",
+      "export function GetData(",
+    ]
+    .map(|definition| code.find(definition).unwrap());
+
+    assert!(positions.windows(2).all(|v| v[0] < v[1]));
+    assert!(code.contains("  kvs?: Partial<Record<A, string>>;\n"));
+    assert!(!code.contains("import "));
+  }
+
+  #[test]
+  fn test_compiler_bundle_strip_const_qualifier() {
+    Compiler::new(
+      vec!["main.thrift".to_string()],
+      "./tests/fixtures/bundle_qualifier".to_string(),
+      "./tests/fixtures/bundle_qualifier/out".to_string(),
+      GenerateOptions::default(),
+    )
+    .bundle("types.ts")
+    .unwrap();
+
+    let code = std::fs::read_to_string("./tests/fixtures/bundle_qualifier/out/types.ts").unwrap();
+
+    assert!(code.contains("export const DEFAULT_TYPE: ItemType = ItemType.Normal;"));
+    assert!(!code.contains("types."));
+  }
+
+  #[test]
+  fn test_compiler_bundle_duplicate_definition() {
+    let err = Compiler::new(
+      vec!["conflict.thrift".to_string()],
+      "./tests/fixtures/bundle_qualifier".to_string(),
+      "./tests/fixtures/bundle_qualifier/out".to_string(),
+      GenerateOptions::default(),
+    )
+    .bundle("conflict.ts")
+    .unwrap_err();
+
+    assert!(matches!(
+      &err,
+      CompileError::DuplicateDefinition { name, first_file, second_file }
+        if name == "ItemType" && first_file == "types.thrift" && second_file == "conflict.thrift"
+    ));
+  }
+
+  #[test]
+  fn test_compiler_include_only_file() {
+    Compiler::new(
+      vec!["index.thrift".to_string()],
+      "./tests/fixtures/barrel".to_string(),
+      "./tests/fixtures/barrel/out".to_string(),
+      GenerateOptions {
+        reexport_includes: true,
+        ..Default::default()
+      },
+    )
+    .compile()
+    .unwrap();
+
+    let code = std::fs::read_to_string("./tests/fixtures/barrel/out/index.ts").unwrap();
+    assert!(code.ends_with(
+      "import * as a from './a';\nexport { a };\nimport * as b from './b';\nexport { b };\n"
+    ));
+    assert!(std::path::Path::new("./tests/fixtures/barrel/out/a.ts").exists());
+    assert!(std::path::Path::new("./tests/fixtures/barrel/out/b.ts").exists());
+  }
+
+  #[test]
+  fn test_compiler_skip_empty_output() {
+    Compiler::new(
+      vec!["index.thrift".to_string()],
+      "./tests/fixtures/barrel".to_string(),
+      "./tests/fixtures/barrel/out_skip".to_string(),
+      GenerateOptions {
+        skip_empty_output: true,
+        ..Default::default()
+      },
+    )
+    .compile()
+    .unwrap();
+
+    assert!(!std::path::Path::new("./tests/fixtures/barrel/out_skip/index.ts").exists());
+    assert!(std::path::Path::new("./tests/fixtures/barrel/out_skip/a.ts").exists());
+    assert!(std::path::Path::new("./tests/fixtures/barrel/out_skip/b.ts").exists());
+  }
+
+  #[test]
+  fn test_compiler_include_above_src_dir() {
+    Compiler::new(
+      vec!["main.thrift".to_string()],
+      "./tests/fixtures/external/src".to_string(),
+      "./tests/fixtures/external/out".to_string(),
+      GenerateOptions::default(),
+    )
+    .compile()
+    .unwrap();
+
+    let code = std::fs::read_to_string("./tests/fixtures/external/out/main.ts").unwrap();
+    assert!(code.contains("import * as base from './_external/common/base';\n"));
+
+    let code =
+      std::fs::read_to_string("./tests/fixtures/external/out/_external/common/base.ts").unwrap();
+    assert!(code.contains("import * as types from '../../sub/types';\n"));
+    assert!(std::path::Path::new("./tests/fixtures/external/out/sub/types.ts").exists());
+  }
+
+  #[test]
+  fn test_compiler_compile_one() {
+    let compiler = Compiler::new(
+      vec![],
+      "./tests/fixtures/compiler".to_string(),
+      "./tests/fixtures/compiler/out_one".to_string(),
+      GenerateOptions::default(),
+    );
+
+    let (code, deps) = compiler.compile_one("service.thrift").unwrap();
+    assert!(code.contains("import * as a from './a';\n"));
+    assert!(code.contains("export interface GetDataReq {\n"));
+    assert_eq!(deps, ["a.thrift"]);
+    assert!(!std::path::Path::new("./tests/fixtures/compiler/out_one").exists());
+
+    let (code, _) = Compiler::new(
+      vec![],
+      "./tests/fixtures/external/src".to_string(),
+      "./tests/fixtures/external/out_one".to_string(),
+      GenerateOptions {
+        header: Some("// Generated from {file}".to_string()),
+        ..Default::default()
+      },
+    )
+    .compile_one("sub/types.thrift")
+    .unwrap();
+    assert!(code.contains("\n// Generated from sub/types.thrift\n\n"));
+
+    let errors = compiler.compile_one("missing.thrift").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], CompileError::Io { .. }));
+    assert!(errors[0]
+      .to_string()
+      .starts_with("Compiler failed: missing.thrift."));
+  }
+
+  #[test]
+  fn test_compiler_compile_io() {
+    let idl = "struct A {\n  1: i32 id\n}\n";
+    let mut output = vec![];
+
+    Compiler::compile_io(idl.as_bytes(), &mut output, GenerateOptions::default()).unwrap();
+
+    let code = String::from_utf8(output).unwrap();
+    assert!(code.starts_with("/* eslint-disable */\n"));
+    assert!(code.ends_with("export interface A {\n  id: number;\n}\n"));
+
+    let err = Compiler::compile_io("struct A {".as_bytes(), vec![], GenerateOptions::default())
+      .unwrap_err();
+    assert!(matches!(err, CompileError::Parse { .. }));
+    assert!(err.to_string().starts_with("Compiler failed: <stdin>."));
+  }
+
+  #[test]
+  fn test_compiler_missing_file() {
+    let err = Compiler::new(
+      vec!["missing.thrift".to_string()],
+      "./tests/fixtures/compiler".to_string(),
+      "./tests/fixtures/compiler/out_missing".to_string(),
+      GenerateOptions::default(),
+    )
+    .compile()
+    .unwrap_err();
+
+    match &err {
+      CompileError::Io { file, error } => {
+        assert_eq!(file, "missing.thrift");
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+      }
+      _ => panic!("unexpected error: {}", err),
+    }
+    assert!(err
+      .to_string()
+      .starts_with("Compiler failed: missing.thrift."));
+  }
+
+  #[test]
+  fn test_compiler_known_annotation_scopes() {
+    let idl = "struct A {\n  1: i32 id (api.query = \"id\", other = \"x\")\n}\n";
+    let options = GenerateOptions {
+      known_annotation_scopes: Some(vec!["api".to_string()]),
+      ..Default::default()
+    };
+
+    let err = Compiler::compile_io(idl.as_bytes(), vec![], options.clone()).unwrap_err();
+    assert!(matches!(err, CompileError::Validation { .. }));
+    assert_eq!(
+      err.to_string(),
+      "Compiler failed: <stdin>. Unknown annotation scope `other` in `other` on `A.id`"
+    );
+
+    Compiler::compile_io(idl.as_bytes(), vec![], GenerateOptions::default()).unwrap();
+  }
+
+  #[test]
+  fn test_compiler_skip_unchanged_output() {
+    let out_dir = "./tests/fixtures/compiler/out_unchanged";
+    let compile = || {
+      Compiler::new(
+        vec!["service.thrift".to_string()],
+        "./tests/fixtures/compiler".to_string(),
+        out_dir.to_string(),
+        GenerateOptions::default(),
+      )
+      .compile()
+      .unwrap()
+    };
+    let modified = |file: &str| {
+      std::fs::metadata(format!("{}/{}", out_dir, file))
+        .unwrap()
+        .modified()
+        .unwrap()
+    };
+
+    compile();
+    let stale_file = format!("{}/stale/old.ts", out_dir);
+    std::fs::create_dir_all(format!("{}/stale", out_dir)).unwrap();
+    std::fs::write(&stale_file, "").unwrap();
+    let service_modified = modified("service.ts");
+    let a_modified = modified("a.ts");
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    compile();
+
+    assert_eq!(modified("service.ts"), service_modified);
+    assert_eq!(modified("a.ts"), a_modified);
+    assert!(!std::path::Path::new(&stale_file).exists());
+    assert!(!std::path::Path::new(&format!("{}/stale", out_dir)).exists());
+
+    std::fs::remove_dir_all(out_dir).unwrap();
+  }
 }
@@ -1,14 +1,19 @@
+use std::{cell::Cell, collections::HashSet};
+
 use nom::{
   branch::alt,
   bytes::complete::{is_not, tag, take_till, take_until},
-  character::complete::{anychar, char, digit1, multispace0, none_of, space0, space1},
-  combinator::{map, opt},
-  error::context,
+  character::complete::{
+    anychar, char, digit1, hex_digit1, multispace0, none_of, one_of, satisfy, space0, space1,
+  },
+  combinator::{fail, map, map_res, not, opt, recognize},
+  error::{context, convert_error, VerboseError, VerboseErrorKind},
   multi::{many0, separated_list1},
-  sequence::{delimited, preceded, separated_pair, tuple},
-  IResult,
+  sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
 };
 
+type IResult<I, O> = nom::IResult<I, O, VerboseError<I>>;
+
 /// # thrift 基础语法
 ///
 /// ```text
@@ -57,8 +62,14 @@ pub enum TopDefinition {
   Namespace(NamespaceDefinition),
   Include(IncludeDefinition),
   Struct(StructDefinition),
+  /// exception 与 struct 的结构相同，只能出现在函数的 `throws` 中
+  Exception(StructDefinition),
+  Union(UnionDefinition),
   Enum(EnumDefinition),
+  Senum(SenumDefinition),
   Service(ServiceDefinition),
+  Typedef(TypedefDefinition),
+  Const(ConstDefinition),
 }
 
 #[derive(Debug)]
@@ -72,11 +83,22 @@ pub struct IncludeDefinition {
   pub path: StringLiteral,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StructDefinition {
   pub name: Identifier,
   pub fields: Vec<FieldDefinition>,
   pub comments: Vec<Comment>,
+  /// `struct A { ... } (k = "v")` 中的注解
+  pub annotations: Option<Annotations>,
+}
+
+/// union 同一时刻只有一个字段有值，字段 id 的规则与 struct 相同
+#[derive(Debug)]
+pub struct UnionDefinition {
+  pub name: Identifier,
+  pub fields: Vec<FieldDefinition>,
+  pub comments: Vec<Comment>,
+  pub annotations: Option<Annotations>,
 }
 
 #[derive(Debug)]
@@ -86,22 +108,65 @@ pub struct EnumDefinition {
   pub comments: Vec<Comment>,
 }
 
+/// 已废弃的 `senum Foo { "a" "b" }`，声明一组字符串常量
+#[derive(Debug)]
+pub struct SenumDefinition {
+  pub name: Identifier,
+  pub values: Vec<StringLiteral>,
+  pub comments: Vec<Comment>,
+}
+
 #[derive(Debug)]
+pub struct TypedefDefinition {
+  pub name: Identifier,
+  pub field_type: ThriftType,
+  pub comments: Vec<Comment>,
+}
+
+#[derive(Debug)]
+pub struct ConstDefinition {
+  pub name: Identifier,
+  pub field_type: ThriftType,
+  pub value: ConstValue,
+  pub comments: Vec<Comment>,
+  /// `const i32 RED = 1 (k = "v")` 中的注解
+  pub annotations: Option<Annotations>,
+}
+
+/// 常量的值，map 按照源码中的顺序保存
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+  Integer(i64),
+  Double(f64),
+  String(StringLiteral),
+  /// 引用其他常量或 enum 成员，如 `ItemType.Normal`
+  Identifier(Identifier),
+  List(Vec<ConstValue>),
+  Map(Vec<(ConstValue, ConstValue)>),
+}
+
+#[derive(Debug, Clone)]
 pub struct FieldDefinition {
   pub name: Identifier,
   pub field_id: FieldId,
   pub field_type: ThriftType,
   pub requiredness: Option<Requiredness>,
+  /// `1: list<i32> (cpp.type = "std::vector") x` 中紧跟在类型之后的注解，只描述类型本身
+  pub type_annotations: Option<Annotations>,
+  /// `1: Item &x` 中的 `&`，C++ 中按引用保存字段，其他语言中没有意义
+  pub reference: bool,
+  /// `1: i32 status = 0` 中的默认值
+  pub default_value: Option<ConstValue>,
   pub comments: Vec<Comment>,
   pub annotations: Option<Annotations>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FieldId {
   pub value: usize,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ThriftType {
   Void,
   String,
@@ -115,6 +180,142 @@ pub enum ThriftType {
   Identifier(Identifier),
 }
 
+impl ThriftDocument {
+  /// 合并另一个文档的定义，同名的定义保留当前文档中的，返回发生冲突的名称
+  ///
+  /// 相同路径的 include 和相同作用域的 namespace 也只保留一个
+  pub fn merge(&mut self, other: ThriftDocument) -> Vec<String> {
+    let mut collisions = vec![];
+
+    for definition in other.body {
+      let exists = self.body.iter().any(|v| match (v, &definition) {
+        (TopDefinition::Namespace(a), TopDefinition::Namespace(b)) => a.scope == b.scope,
+        (TopDefinition::Include(a), TopDefinition::Include(b)) => a.path == b.path,
+        _ => v.name().is_some() && v.name() == definition.name(),
+      });
+
+      if !exists {
+        self.body.push(definition);
+      } else if let Some(name) = definition.name() {
+        collisions.push(name.to_string());
+      }
+    }
+
+    collisions
+  }
+}
+
+impl TopDefinition {
+  /// 定义的名称，namespace 和 include 没有名称
+  pub fn name(&self) -> Option<&str> {
+    match self {
+      TopDefinition::Namespace(_) | TopDefinition::Include(_) => None,
+      TopDefinition::Struct(v) | TopDefinition::Exception(v) => Some(&v.name.value),
+      TopDefinition::Union(v) => Some(&v.name.value),
+      TopDefinition::Enum(v) => Some(&v.name.value),
+      TopDefinition::Senum(v) => Some(&v.name.value),
+      TopDefinition::Service(v) => Some(&v.name.value),
+      TopDefinition::Typedef(v) => Some(&v.name.value),
+      TopDefinition::Const(v) => Some(&v.name.value),
+    }
+  }
+}
+
+impl ThriftType {
+  /// 展开 typedef 别名，得到规范化后的类型，结构相同的类型展开后可以直接比较
+  pub fn resolve(&self, document: &ThriftDocument) -> ThriftType {
+    self.resolve_with_seen(document, &mut vec![])
+  }
+
+  fn collect_type_names(&self, names: &mut HashSet<String>) {
+    match self {
+      ThriftType::List(ty) => ty.collect_type_names(names),
+      ThriftType::Map(key_ty, value_ty) => {
+        key_ty.collect_type_names(names);
+        value_ty.collect_type_names(names);
+      }
+      ThriftType::Identifier(id) => {
+        names.insert(id.value.clone());
+      }
+      _ => {}
+    }
+  }
+
+  fn resolve_with_seen<'a>(
+    &self,
+    document: &'a ThriftDocument,
+    seen: &mut Vec<&'a str>,
+  ) -> ThriftType {
+    match self {
+      ThriftType::List(ty) => ThriftType::List(Box::new(ty.resolve_with_seen(document, seen))),
+      ThriftType::Map(key_ty, value_ty) => ThriftType::Map(
+        Box::new(key_ty.resolve_with_seen(document, seen)),
+        Box::new(value_ty.resolve_with_seen(document, seen)),
+      ),
+      ThriftType::Identifier(id) => {
+        let typedef = document
+          .body
+          .iter()
+          .find_map(|definition| match definition {
+            TopDefinition::Typedef(typedef) if typedef.name.value == id.value => Some(typedef),
+            _ => None,
+          });
+
+        match typedef {
+          // 遇到循环引用时停止展开
+          Some(typedef) if !seen.contains(&typedef.name.value.as_str()) => {
+            seen.push(&typedef.name.value);
+            let ret = typedef.field_type.resolve_with_seen(document, seen);
+            seen.pop();
+            ret
+          }
+          _ => self.clone(),
+        }
+      }
+      _ => self.clone(),
+    }
+  }
+}
+
+impl StructDefinition {
+  /// 字段中引用的类型名称，如 `a.A`、`ItemType`，移动 struct 到其他文件时可以据此补全 include
+  ///
+  /// AST 中没有保存位置信息，clone 得到的子树与原来的完全相同
+  pub fn referenced_type_names(&self) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    for field in &self.fields {
+      field.field_type.collect_type_names(&mut names);
+    }
+
+    names
+  }
+}
+
+impl EnumDefinition {
+  /// 每个成员对应的值，没有显式赋值的成员在上一个成员的基础上加一。
+  /// 上一个成员为 `i64::MAX` 时无法继续编号，返回的结果在该成员之前截止，`validate` 会报告这种情况
+  pub fn member_values(&self) -> Vec<i64> {
+    let mut values = vec![];
+    let mut next = Some(0);
+
+    for member in &self.members {
+      let initializer = member
+        .initializer
+        .as_ref()
+        .and_then(|initializer| initializer.value.parse::<i64>().ok());
+      let Some(value) = initializer.or(next) else {
+        break;
+      };
+
+      values.push(value);
+      next = value.checked_add(1);
+    }
+
+    values
+  }
+}
+
 #[derive(Debug)]
 pub struct EnumMember {
   pub name: Identifier,
@@ -130,6 +331,8 @@ pub struct IntegerLiteral {
 #[derive(Debug)]
 pub struct ServiceDefinition {
   pub name: Identifier,
+  /// `service A extends B` 中的 `B`
+  pub extends: Option<Identifier>,
   pub functions: Vec<FunctionDefinition>,
   pub comments: Vec<Comment>,
 }
@@ -137,24 +340,28 @@ pub struct ServiceDefinition {
 #[derive(Debug)]
 pub struct FunctionDefinition {
   pub name: Identifier,
+  /// `oneway void Notify(...)`，调用方不等待响应
+  pub oneway: bool,
   pub return_type: ThriftType,
   pub fields: Vec<FieldDefinition>,
+  /// `throws (1: NotFound e1)` 中声明的异常，字段 id 与返回值的 0 一起组成结果
+  pub throws: Vec<FieldDefinition>,
   pub comments: Vec<Comment>,
   pub annotations: Option<Annotations>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Identifier {
   pub value: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Requiredness {
   Optional,
   Required,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Comment {
   Line(CommentLine),
   Block(CommentBlock),
@@ -182,32 +389,285 @@ impl Comment {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommentLine {
   pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommentBlock {
   pub value: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Annotations {
   pub annotations: Vec<Annotation>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Annotation {
   pub name: Identifier,
   pub value: StringLiteral,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct StringLiteral {
   pub value: String,
 }
 
+impl ThriftType {
+  pub fn to_thrift(&self) -> String {
+    match self {
+      ThriftType::Void => "void".to_string(),
+      ThriftType::String => "string".to_string(),
+      ThriftType::I16 => "i16".to_string(),
+      ThriftType::I32 => "i32".to_string(),
+      ThriftType::I64 => "i64".to_string(),
+      ThriftType::Double => "double".to_string(),
+      ThriftType::Bool => "bool".to_string(),
+      ThriftType::List(ty) => format!("list<{}>", ty.to_thrift()),
+      ThriftType::Map(key_ty, value_ty) => {
+        format!("map<{}, {}>", key_ty.to_thrift(), value_ty.to_thrift())
+      }
+      ThriftType::Identifier(id) => id.value.clone(),
+    }
+  }
+}
+
+impl ConstValue {
+  pub fn to_thrift(&self) -> String {
+    match self {
+      ConstValue::Integer(v) => v.to_string(),
+      ConstValue::Double(v) => format!("{:?}", v),
+      ConstValue::String(v) => {
+        format!("\"{}\"", v.value.replace('\\', "\\\\").replace('"', "\\\""))
+      }
+      ConstValue::Identifier(id) => id.value.clone(),
+      ConstValue::List(items) => format!(
+        "[{}]",
+        items
+          .iter()
+          .map(|v| v.to_thrift())
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+      ConstValue::Map(entries) => format!(
+        "{{{}}}",
+        entries
+          .iter()
+          .map(|(k, v)| format!("{}: {}", k.to_thrift(), v.to_thrift()))
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+    }
+  }
+}
+
+impl Comment {
+  pub fn to_thrift(&self) -> String {
+    match self {
+      Comment::Line(line) => format!("// {}", line.value),
+      Comment::Block(block) => format!("/* {} */", block.value.join("\n")),
+    }
+  }
+}
+
+impl Annotations {
+  pub fn to_thrift(&self) -> String {
+    let annotations = self
+      .annotations
+      .iter()
+      .map(|annotation| {
+        format!(
+          "{}=\"{}\"",
+          annotation.name.value,
+          annotation
+            .value
+            .value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+        )
+      })
+      .collect::<Vec<_>>();
+
+    format!("({})", annotations.join(", "))
+  }
+}
+
+impl FieldDefinition {
+  /// 将单个字段还原为 thrift 语法，注释会放在字段的上方
+  pub fn to_thrift(&self) -> String {
+    let mut code = String::new();
+
+    for comment in &self.comments {
+      code.push_str(&comment.to_thrift());
+      code.push('\n');
+    }
+
+    code.push_str(&format!("{}: ", self.field_id.value));
+
+    match self.requiredness {
+      Some(Requiredness::Optional) => code.push_str("optional "),
+      Some(Requiredness::Required) => code.push_str("required "),
+      None => {}
+    }
+
+    code.push_str(&self.field_type.to_thrift());
+
+    if let Some(type_annotations) = &self.type_annotations {
+      code.push(' ');
+      code.push_str(&type_annotations.to_thrift());
+    }
+
+    code.push(' ');
+
+    if self.reference {
+      code.push('&');
+    }
+
+    code.push_str(&self.name.value);
+
+    if let Some(default_value) = &self.default_value {
+      code.push_str(&format!(" = {}", default_value.to_thrift()));
+    }
+
+    if let Some(annotations) = &self.annotations {
+      code.push(' ');
+      code.push_str(&annotations.to_thrift());
+    }
+
+    code
+  }
+}
+
+impl FunctionDefinition {
+  /// 将函数还原为 thrift 语法，注解保持源码中的顺序
+  pub fn to_thrift(&self) -> String {
+    let mut code = String::new();
+
+    for comment in &self.comments {
+      code.push_str(&comment.to_thrift());
+      code.push('\n');
+    }
+
+    let fields = |fields: &[FieldDefinition]| {
+      fields
+        .iter()
+        .map(|field| field.to_thrift())
+        .collect::<Vec<_>>()
+        .join(", ")
+    };
+
+    if self.oneway {
+      code.push_str("oneway ");
+    }
+
+    code.push_str(&format!(
+      "{} {}({})",
+      self.return_type.to_thrift(),
+      self.name.value,
+      fields(&self.fields)
+    ));
+
+    if !self.throws.is_empty() {
+      code.push_str(&format!(" throws ({})", fields(&self.throws)));
+    }
+
+    if let Some(annotations) = &self.annotations {
+      code.push(' ');
+      code.push_str(&annotations.to_thrift());
+    }
+
+    code
+  }
+}
+
+/// 解析时的可选行为，默认值与 thrift 官方编译器保持一致
+///
+/// 目前只支持单引号字符串和整数分隔符，未知关键字的处理和保留注释节点的模式还没有实现
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseOptions {
+  /// 是否允许使用单引号的字符串，如 `include 'a.thrift'`
+  pub single_quotes: bool,
+  /// 是否允许整数中使用 `_` 分隔，如 `1_000`
+  pub underscore_separators: bool,
+}
+
+impl Default for ParseOptions {
+  fn default() -> Self {
+    Self {
+      single_quotes: true,
+      underscore_separators: false,
+    }
+  }
+}
+
+thread_local! {
+  // 解析函数都是普通函数，通过线程局部变量读取当前的解析选项。
+  // 只在 `Parser::parse_with_options` 执行期间有效，由 `ParseOptionsGuard` 负责设置和恢复，
+  // 解析函数不能在其他线程中执行，也不能在 `parse_with_options` 之外读取
+  static PARSE_OPTIONS: Cell<ParseOptions> = Cell::new(ParseOptions::default());
+  // 当前容器类型的嵌套深度
+  static TYPE_DEPTH: Cell<usize> = const { Cell::new(0) };
+  // 当前常量中 list 和 map 的嵌套深度
+  static CONST_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 容器类型以及常量中 list 和 map 允许的最大嵌套深度，解析是递归的，过深的嵌套会导致栈溢出。
+/// 生成代码时对类型和常量的处理同样是递归的，依赖这里的限制保证递归深度有上限
+pub const MAX_TYPE_DEPTH: usize = 64;
+
+/// 超过 `MAX_TYPE_DEPTH` 时的错误信息，`VerboseErrorKind::Context` 只接受静态字符串
+const TYPE_DEPTH_ERROR: &str =
+  "type nesting too deep, at most 64 nested container types are allowed";
+
+/// 常量中 list 和 map 超过 `MAX_TYPE_DEPTH` 层时的错误信息
+const CONST_DEPTH_ERROR: &str =
+  "const value nesting too deep, at most 64 nested lists and maps are allowed";
+
+fn parse_options() -> ParseOptions {
+  PARSE_OPTIONS.with(|options| options.get())
+}
+
+/// 设置当前线程的解析选项，drop 时恢复之前的值，解析过程中 panic 或嵌套解析时也不会遗留错误的选项
+struct ParseOptionsGuard(ParseOptions);
+
+impl ParseOptionsGuard {
+  fn new(options: ParseOptions) -> Self {
+    Self(PARSE_OPTIONS.with(|v| v.replace(options)))
+  }
+}
+
+impl Drop for ParseOptionsGuard {
+  fn drop(&mut self) {
+    PARSE_OPTIONS.with(|v| v.set(self.0));
+  }
+}
+
+/// 十进制整数，开启 `underscore_separators` 时会去掉其中的 `_`
+fn decimal_digits(i: &str) -> IResult<&str, String> {
+  if parse_options().underscore_separators {
+    map(
+      recognize(tuple((digit1, many0(preceded(char('_'), digit1))))),
+      |v: &str| v.replace('_', ""),
+    )(i)
+  } else {
+    map(digit1, |v: &str| v.to_string())(i)
+  }
+}
+
+fn single_quoted_chars(i: &str) -> IResult<&str, Vec<char>> {
+  if !parse_options().single_quotes {
+    return fail(i);
+  }
+
+  delimited(
+    char('\''),
+    many0(alt((js_escaped_char, none_of("\'")))),
+    char('\''),
+  )(i)
+}
+
 fn js_escaped_char(input: &str) -> IResult<&str, char> {
   preceded(char('\\'), anychar)(input)
 }
@@ -222,11 +682,7 @@ fn string_literal(i: &str) -> IResult<&str, StringLiteral> {
           many0(alt((js_escaped_char, none_of("\"")))),
           char('"'),
         ),
-        delimited(
-          char('\''),
-          many0(alt((js_escaped_char, none_of("\'")))),
-          char('\''),
-        ),
+        single_quoted_chars,
       )),
       |v| StringLiteral {
         value: v.into_iter().collect::<String>(),
@@ -239,7 +695,7 @@ fn identifier(i: &str) -> IResult<&str, Identifier> {
   context(
     "identifier",
     map(
-      preceded(multispace0, is_not(" \t\n\r-=(){}[]<>,;")),
+      preceded(multispace0, is_not(" \t\n\r-=(){}[]<>,;:")),
       |v: &str| Identifier {
         value: v.to_string(),
       },
@@ -276,11 +732,12 @@ fn include_definition(i: &str) -> IResult<&str, IncludeDefinition> {
 fn field_id(i: &str) -> IResult<&str, FieldId> {
   context(
     "field_id",
-    map(delimited(multispace0, digit1, tag(":")), |v: &str| {
-      FieldId {
+    map(
+      delimited(multispace0, decimal_digits, preceded(space0, tag(":"))),
+      |v| FieldId {
         value: v.parse::<usize>().unwrap(),
-      }
-    }),
+      },
+    ),
   )(i)
 }
 
@@ -288,7 +745,12 @@ fn requiredness(i: &str) -> IResult<&str, Requiredness> {
   context(
     "requiredness",
     map(
-      preceded(space1, alt((tag("optional"), tag("required")))),
+      delimited(
+        space0,
+        alt((tag("optional"), tag("required"))),
+        // 避免把 optionalType 这种标识符的前缀当成关键字
+        not(satisfy(|c| c.is_alphanumeric() || c == '_')),
+      ),
       |v| match v {
         "optional" => Requiredness::Optional,
         "required" => Requiredness::Required,
@@ -298,12 +760,44 @@ fn requiredness(i: &str) -> IResult<&str, Requiredness> {
   )(i)
 }
 
+/// 容器类型后可以跟 `cpp_type "..."`，只对 C++ 有意义，直接丢弃
+fn container_suffix(i: &str) -> IResult<&str, ()> {
+  map(
+    opt(preceded(
+      tuple((space0, tag("cpp_type"), space1)),
+      string_literal,
+    )),
+    |_| (),
+  )(i)
+}
+
+/// 类型以及紧跟在类型之后的类型注解
+fn annotated_type(i: &str) -> IResult<&str, (ThriftType, Option<Annotations>)> {
+  pair(thrift_type, opt(annotations))(i)
+}
+
+/// 除字段外，其他位置的类型注解只对 C++ 等其他语言有意义，直接丢弃
+fn unannotated_type(i: &str) -> IResult<&str, ThriftType> {
+  map(annotated_type, |v| v.0)(i)
+}
+
+/// 容器的元素类型，同样可以带上类型注解
+fn element_type(i: &str) -> IResult<&str, ThriftType> {
+  unannotated_type(i)
+}
+
 fn list_type(i: &str) -> IResult<&str, Box<ThriftType>> {
   context(
     "list_type",
     map(
-      preceded(tag("list"), delimited(char('<'), thrift_type, char('>'))),
-      |v| Box::new(v),
+      terminated(
+        preceded(
+          pair(tag("list"), space0),
+          delimited(char('<'), element_type, preceded(space0, char('>'))),
+        ),
+        container_suffix,
+      ),
+      Box::new,
     ),
   )(i)
 }
@@ -312,17 +806,20 @@ fn map_type(i: &str) -> IResult<&str, (Box<ThriftType>, Box<ThriftType>)> {
   context(
     "map_type",
     map(
-      preceded(
-        tag("map"),
-        delimited(
-          char('<'),
-          separated_pair(
-            thrift_type,
-            delimited(space0, tag(","), space0),
-            thrift_type,
+      terminated(
+        preceded(
+          pair(tag("map"), space0),
+          delimited(
+            char('<'),
+            separated_pair(
+              element_type,
+              delimited(space0, tag(","), space0),
+              element_type,
+            ),
+            preceded(space0, char('>')),
           ),
-          char('>'),
         ),
+        container_suffix,
       ),
       |v| (Box::new(v.0), Box::new(v.1)),
     ),
@@ -330,6 +827,21 @@ fn map_type(i: &str) -> IResult<&str, (Box<ThriftType>, Box<ThriftType>)> {
 }
 
 fn thrift_type(i: &str) -> IResult<&str, ThriftType> {
+  let depth = TYPE_DEPTH.with(|v| v.get());
+  // 最内层的非容器类型也会经过这里，所以允许的深度比容器的层数多一层
+  if depth > MAX_TYPE_DEPTH {
+    return Err(nom::Err::Failure(VerboseError {
+      errors: vec![(i, VerboseErrorKind::Context(TYPE_DEPTH_ERROR))],
+    }));
+  }
+
+  TYPE_DEPTH.with(|v| v.set(depth + 1));
+  let ret = thrift_type_without_depth(i);
+  TYPE_DEPTH.with(|v| v.set(depth));
+  ret
+}
+
+fn thrift_type_without_depth(i: &str) -> IResult<&str, ThriftType> {
   context(
     "field_type",
     preceded(
@@ -342,9 +854,9 @@ fn thrift_type(i: &str) -> IResult<&str, ThriftType> {
         map(tag("i64"), |_| ThriftType::I64),
         map(tag("double"), |_| ThriftType::Double),
         map(tag("bool"), |_| ThriftType::Bool),
-        map(list_type, |v| ThriftType::List(v)),
+        map(list_type, ThriftType::List),
         map(map_type, |v| ThriftType::Map(v.0, v.1)),
-        map(identifier, |v| ThriftType::Identifier(v)),
+        map(identifier, ThriftType::Identifier),
       )),
     ),
   )(i)
@@ -390,8 +902,8 @@ fn comment(i: &str) -> IResult<&str, Comment> {
   context(
     "comment",
     alt((
-      map(comment_line, |v| Comment::Line(v)),
-      map(comment_block, |v| Comment::Block(v)),
+      map(comment_line, Comment::Line),
+      map(comment_block, Comment::Block),
     )),
   )(i)
 }
@@ -413,6 +925,16 @@ fn comment_inline(i: &str) -> IResult<&str, Comment> {
   )(i)
 }
 
+/// 声明中间的注释，如 `1: /* id */ i32 x`，直接跳过
+fn comment_gap(i: &str) -> IResult<&str, ()> {
+  map(many0(terminated(comment, multispace0)), |_| ())(i)
+}
+
+/// 列表的结束符号，之前没有对应成员的注释直接丢弃
+fn closing<'a>(token: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+  preceded(pair(comment_gap, multispace0), tag(token))
+}
+
 fn annotation(i: &str) -> IResult<&str, Annotation> {
   context(
     "annotation",
@@ -436,7 +958,11 @@ fn annotations(i: &str) -> IResult<&str, Annotations> {
     map(
       preceded(
         space0,
-        delimited(tag("("), separated_list1(tag(","), annotation), tag(")")),
+        delimited(
+          tag("("),
+          separated_list1(preceded(multispace0, tag(",")), annotation),
+          preceded(tuple((multispace0, opt(tag(",")), multispace0)), tag(")")),
+        ),
       ),
       |v| Annotations { annotations: v },
     ),
@@ -450,24 +976,32 @@ fn field_definition(i: &str) -> IResult<&str, FieldDefinition> {
       tuple((
         many0(comment),
         field_id,
-        opt(requiredness),
-        thrift_type,
-        identifier,
-        opt(annotations),
+        preceded(comment_gap, opt(requiredness)),
+        preceded(comment_gap, annotated_type),
+        preceded(
+          comment_gap,
+          pair(opt(preceded(multispace0, char('&'))), identifier),
+        ),
+        opt(preceded(delimited(space0, tag("="), space0), const_value)),
+        opt(preceded(comment_gap, annotations)),
+        opt(preceded(space0, one_of(",;"))),
         opt(comment_inline),
       )),
       |mut v| {
-        if let Some(inline) = v.6 {
+        if let Some(inline) = v.8 {
           v.0.push(inline);
         };
 
         FieldDefinition {
           field_id: v.1,
           requiredness: v.2,
-          field_type: v.3,
-          name: v.4,
+          field_type: v.3 .0,
+          type_annotations: v.3 .1,
+          reference: v.4 .0.is_some(),
+          name: v.4 .1,
+          default_value: v.5,
           comments: v.0,
-          annotations: v.5,
+          annotations: v.6,
         }
       },
     ),
@@ -487,8 +1021,9 @@ fn struct_definition_without_comments(i: &str) -> IResult<&str, StructDefinition
             delimited(
               preceded(multispace0, tag("{")),
               many0(field_definition),
-              preceded(multispace0, tag("}")),
+              closing("}"),
             ),
+            opt(annotations),
           )),
         ),
       ),
@@ -496,6 +1031,7 @@ fn struct_definition_without_comments(i: &str) -> IResult<&str, StructDefinition
         name: v.0,
         fields: v.1,
         comments: vec![],
+        annotations: v.2,
       },
     ),
   )(i)
@@ -517,72 +1053,44 @@ fn struct_definition(i: &str) -> IResult<&str, StructDefinition> {
   )(i)
 }
 
-fn enum_member(i: &str) -> IResult<&str, EnumMember> {
-  context(
-    "enum_member",
-    map(
-      tuple((
-        many0(comment),
-        tuple((
-          identifier,
-          opt(preceded(delimited(space0, tag("="), space0), digit1)),
-        )),
-        opt(tag(",")),
-        opt(comment_inline),
-      )),
-      |mut v| {
-        if let Some(inline) = v.3 {
-          v.0.push(inline);
-        }
-
-        EnumMember {
-          name: v.1 .0,
-          initializer: v.1 .1.map(|str| IntegerLiteral {
-            value: str.to_string(),
-          }),
-          comments: v.0,
-        }
-      },
-    ),
-  )(i)
-}
-
-fn enum_definition_without_comments(i: &str) -> IResult<&str, EnumDefinition> {
+fn exception_definition_without_comments(i: &str) -> IResult<&str, StructDefinition> {
   context(
-    "enum_definition",
+    "exception_definition",
     map(
       preceded(
         multispace0,
         preceded(
-          tag("enum"),
+          tag("exception"),
           tuple((
             identifier,
             delimited(
               preceded(multispace0, tag("{")),
-              many0(enum_member),
-              preceded(multispace0, tag("}")),
+              many0(field_definition),
+              closing("}"),
             ),
+            opt(annotations),
           )),
         ),
       ),
-      |v| EnumDefinition {
+      |v| StructDefinition {
         name: v.0,
-        members: v.1,
+        fields: v.1,
         comments: vec![],
+        annotations: v.2,
       },
     ),
   )(i)
 }
 
-fn enum_definition(i: &str) -> IResult<&str, EnumDefinition> {
+fn exception_definition(i: &str) -> IResult<&str, StructDefinition> {
   context(
-    "enum_definition",
+    "exception_definition",
     map(
       preceded(
         multispace0,
-        tuple((many0(comment), enum_definition_without_comments)),
+        tuple((many0(comment), exception_definition_without_comments)),
       ),
-      |v| EnumDefinition {
+      |v| StructDefinition {
         comments: v.0,
         ..v.1
       },
@@ -590,29 +1098,277 @@ fn enum_definition(i: &str) -> IResult<&str, EnumDefinition> {
   )(i)
 }
 
-fn function_definition_without_comments(i: &str) -> IResult<&str, FunctionDefinition> {
+/// union 同一时刻只有一个字段有值，字段不能是 required 的
+fn union_field_definition(i: &str) -> IResult<&str, FieldDefinition> {
+  let required = tuple((
+    many0(comment),
+    field_id,
+    preceded(comment_gap, requiredness),
+  ))(i);
+
+  if let Ok((_, (_, _, Requiredness::Required))) = required {
+    return Err(nom::Err::Failure(VerboseError {
+      errors: vec![(
+        i.trim_start(),
+        VerboseErrorKind::Context("union members cannot be required"),
+      )],
+    }));
+  }
+
+  field_definition(i)
+}
+
+fn union_definition_without_comments(i: &str) -> IResult<&str, UnionDefinition> {
   context(
-    "function_definition",
+    "union_definition",
+    map(
+      preceded(
+        multispace0,
+        preceded(
+          tag("union"),
+          tuple((
+            identifier,
+            delimited(
+              preceded(multispace0, tag("{")),
+              many0(union_field_definition),
+              closing("}"),
+            ),
+            opt(annotations),
+          )),
+        ),
+      ),
+      |v| UnionDefinition {
+        name: v.0,
+        fields: v.1,
+        comments: vec![],
+        annotations: v.2,
+      },
+    ),
+  )(i)
+}
+
+fn union_definition(i: &str) -> IResult<&str, UnionDefinition> {
+  context(
+    "union_definition",
+    map(
+      preceded(
+        multispace0,
+        tuple((many0(comment), union_definition_without_comments)),
+      ),
+      |v| UnionDefinition {
+        comments: v.0,
+        ..v.1
+      },
+    ),
+  )(i)
+}
+
+/// enum 的取值，整数之间可以使用 `+`、`<<`、`|` 组成表达式，优先级与 C 相同
+fn enum_value(i: &str) -> IResult<&str, String> {
+  context("enum_value", map(enum_value_or, |v| v.to_string()))(i)
+}
+
+fn enum_value_or(i: &str) -> IResult<&str, i64> {
+  enum_value_operation(i, enum_value_shift, "|", |a, b| Some(a | b))
+}
+
+fn enum_value_shift(i: &str) -> IResult<&str, i64> {
+  enum_value_operation(i, enum_value_sum, "<<", |a, b| {
+    (0..63)
+      .contains(&b)
+      .then(|| a.checked_mul(1 << b))
+      .flatten()
+  })
+}
+
+fn enum_value_sum(i: &str) -> IResult<&str, i64> {
+  enum_value_operation(i, enum_value_term, "+", i64::checked_add)
+}
+
+fn enum_value_term(i: &str) -> IResult<&str, i64> {
+  let (left, digits) = decimal_digits(i)?;
+
+  match digits.parse::<i64>() {
+    Ok(value) => Ok((left, value)),
+    Err(_) => Err(enum_value_overflow(i)),
+  }
+}
+
+/// 解析 `operand (operator operand)*`，从左到右依次计算
+fn enum_value_operation<'a>(
+  i: &'a str,
+  operand: fn(&'a str) -> IResult<&'a str, i64>,
+  operator: &'static str,
+  apply: fn(i64, i64) -> Option<i64>,
+) -> IResult<&'a str, i64> {
+  let (mut i, mut value) = operand(i)?;
+
+  loop {
+    match preceded(delimited(space0, tag(operator), space0), operand)(i) {
+      Ok((left, rhs)) => {
+        value = apply(value, rhs).ok_or_else(|| enum_value_overflow(i))?;
+        i = left;
+      }
+      Err(nom::Err::Error(_)) => return Ok((i, value)),
+      Err(err) => return Err(err),
+    }
+  }
+}
+
+fn enum_value_overflow(i: &str) -> nom::Err<VerboseError<&str>> {
+  nom::Err::Failure(VerboseError {
+    errors: vec![(i, VerboseErrorKind::Context("enum value out of i64 range"))],
+  })
+}
+
+fn enum_member(i: &str) -> IResult<&str, EnumMember> {
+  context(
+    "enum_member",
+    map(
+      tuple((
+        many0(comment),
+        tuple((
+          identifier,
+          opt(preceded(delimited(space0, tag("="), space0), enum_value)),
+        )),
+        opt(preceded(space0, alt((tag(","), tag(";"))))),
+        opt(comment_inline),
+      )),
+      |mut v| {
+        if let Some(inline) = v.3 {
+          v.0.push(inline);
+        }
+
+        EnumMember {
+          name: v.1 .0,
+          initializer: v.1 .1.map(|value| IntegerLiteral { value }),
+          comments: v.0,
+        }
+      },
+    ),
+  )(i)
+}
+
+fn enum_definition_without_comments(i: &str) -> IResult<&str, EnumDefinition> {
+  context(
+    "enum_definition",
+    map(
+      preceded(
+        multispace0,
+        preceded(
+          tag("enum"),
+          tuple((
+            identifier,
+            delimited(
+              preceded(multispace0, tag("{")),
+              many0(enum_member),
+              closing("}"),
+            ),
+          )),
+        ),
+      ),
+      |v| EnumDefinition {
+        name: v.0,
+        members: v.1,
+        comments: vec![],
+      },
+    ),
+  )(i)
+}
+
+fn senum_definition_without_comments(i: &str) -> IResult<&str, SenumDefinition> {
+  context(
+    "senum_definition",
+    map(
+      preceded(
+        multispace0,
+        preceded(
+          tag("senum"),
+          tuple((
+            identifier,
+            delimited(
+              preceded(multispace0, tag("{")),
+              many0(delimited(
+                comment_gap,
+                preceded(multispace0, string_literal),
+                opt(list_separator),
+              )),
+              closing("}"),
+            ),
+          )),
+        ),
+      ),
+      |v| SenumDefinition {
+        name: v.0,
+        values: v.1,
+        comments: vec![],
+      },
+    ),
+  )(i)
+}
+
+fn senum_definition(i: &str) -> IResult<&str, SenumDefinition> {
+  context(
+    "senum_definition",
+    map(
+      preceded(
+        multispace0,
+        tuple((many0(comment), senum_definition_without_comments)),
+      ),
+      |v| SenumDefinition {
+        comments: v.0,
+        ..v.1
+      },
+    ),
+  )(i)
+}
+
+fn enum_definition(i: &str) -> IResult<&str, EnumDefinition> {
+  context(
+    "enum_definition",
+    map(
+      preceded(
+        multispace0,
+        tuple((many0(comment), enum_definition_without_comments)),
+      ),
+      |v| EnumDefinition {
+        comments: v.0,
+        ..v.1
+      },
+    ),
+  )(i)
+}
+
+fn function_definition_without_comments(i: &str) -> IResult<&str, FunctionDefinition> {
+  context(
+    "function_definition",
     map(
       preceded(
         multispace0,
         tuple((
-          thrift_type,
+          opt(terminated(tag("oneway"), space1)),
+          unannotated_type,
           identifier,
           delimited(
             preceded(space0, tag("(")),
             many0(field_definition),
-            tag(")"),
+            closing(")"),
           ),
+          opt(preceded(
+            tuple((multispace0, tag("throws"), space0)),
+            delimited(tag("("), many0(field_definition), closing(")")),
+          )),
           opt(annotations),
         )),
       ),
       |v| FunctionDefinition {
-        name: v.1,
-        return_type: v.0,
-        fields: v.2,
+        name: v.2,
+        oneway: v.0.is_some(),
+        return_type: v.1,
+        fields: v.3,
+        throws: v.4.unwrap_or_default(),
         comments: vec![],
-        annotations: v.3,
+        annotations: v.5,
       },
     ),
   )(i)
@@ -644,17 +1400,22 @@ fn service_definition_without_comments(i: &str) -> IResult<&str, ServiceDefiniti
           tag("service"),
           tuple((
             identifier,
+            opt(preceded(
+              tuple((multispace0, tag("extends"), space1)),
+              identifier,
+            )),
             delimited(
               preceded(multispace0, tag("{")),
               many0(function_definition),
-              preceded(multispace0, tag("}")),
+              closing("}"),
             ),
           )),
         ),
       ),
       |v| ServiceDefinition {
         name: v.0,
-        functions: v.1,
+        extends: v.1,
+        functions: v.2,
         comments: vec![],
       },
     ),
@@ -677,17 +1438,205 @@ fn service_definition(i: &str) -> IResult<&str, ServiceDefinition> {
   )(i)
 }
 
+fn typedef_definition_without_comments(i: &str) -> IResult<&str, TypedefDefinition> {
+  context(
+    "typedef_definition",
+    map(
+      preceded(
+        multispace0,
+        preceded(tag("typedef"), tuple((unannotated_type, identifier))),
+      ),
+      |v| TypedefDefinition {
+        name: v.1,
+        field_type: v.0,
+        comments: vec![],
+      },
+    ),
+  )(i)
+}
+
+fn typedef_definition(i: &str) -> IResult<&str, TypedefDefinition> {
+  context(
+    "typedef_definition",
+    map(
+      preceded(
+        multispace0,
+        tuple((many0(comment), typedef_definition_without_comments)),
+      ),
+      |v| TypedefDefinition {
+        comments: v.0,
+        ..v.1
+      },
+    ),
+  )(i)
+}
+
+fn integer_constant(i: &str) -> IResult<&str, i64> {
+  let (left, (sign, digits)) = tuple((
+    opt(one_of("+-")),
+    alt((
+      map(
+        recognize(pair(alt((tag("0x"), tag("0X"))), hex_digit1)),
+        |v: &str| v.to_string(),
+      ),
+      decimal_digits,
+    )),
+  ))(i)?;
+
+  let sign = if sign == Some('-') { "-" } else { "" };
+  let value = match digits
+    .strip_prefix("0x")
+    .or_else(|| digits.strip_prefix("0X"))
+  {
+    Some(hex) => i64::from_str_radix(&format!("{}{}", sign, hex), 16),
+    None => format!("{}{}", sign, digits).parse::<i64>(),
+  };
+
+  match value {
+    Ok(value) => Ok((left, value)),
+    // 超出范围时直接失败，不再尝试其他分支，以便报告准确的位置
+    Err(_) => Err(nom::Err::Failure(VerboseError {
+      errors: vec![(
+        i,
+        VerboseErrorKind::Context("integer literal out of i64 range"),
+      )],
+    })),
+  }
+}
+
+fn double_constant(i: &str) -> IResult<&str, f64> {
+  let exponent = || tuple((one_of("eE"), opt(one_of("+-")), digit1));
+
+  map_res(
+    recognize(tuple((
+      opt(one_of("+-")),
+      digit1,
+      alt((
+        recognize(tuple((char('.'), digit1, opt(exponent())))),
+        recognize(exponent()),
+      )),
+    ))),
+    |v: &str| v.parse::<f64>(),
+  )(i)
+}
+
+fn list_separator(i: &str) -> IResult<&str, char> {
+  preceded(multispace0, one_of(",;"))(i)
+}
+
+fn const_value(i: &str) -> IResult<&str, ConstValue> {
+  let depth = CONST_DEPTH.with(|v| v.get());
+  // 与 `thrift_type` 相同，最内层的字面量也会经过这里
+  if depth > MAX_TYPE_DEPTH {
+    return Err(nom::Err::Failure(VerboseError {
+      errors: vec![(i, VerboseErrorKind::Context(CONST_DEPTH_ERROR))],
+    }));
+  }
+
+  CONST_DEPTH.with(|v| v.set(depth + 1));
+  let ret = const_value_without_depth(i);
+  CONST_DEPTH.with(|v| v.set(depth));
+  ret
+}
+
+fn const_value_without_depth(i: &str) -> IResult<&str, ConstValue> {
+  context(
+    "const_value",
+    preceded(
+      multispace0,
+      alt((
+        map(double_constant, ConstValue::Double),
+        map(integer_constant, ConstValue::Integer),
+        map(string_literal, ConstValue::String),
+        map(
+          delimited(
+            char('['),
+            many0(terminated(const_value, opt(list_separator))),
+            preceded(multispace0, char(']')),
+          ),
+          ConstValue::List,
+        ),
+        map(
+          delimited(
+            char('{'),
+            many0(terminated(
+              separated_pair(const_value, preceded(multispace0, char(':')), const_value),
+              opt(list_separator),
+            )),
+            preceded(multispace0, char('}')),
+          ),
+          ConstValue::Map,
+        ),
+        map(identifier, ConstValue::Identifier),
+      )),
+    ),
+  )(i)
+}
+
+fn const_definition_without_comments(i: &str) -> IResult<&str, ConstDefinition> {
+  context(
+    "const_definition",
+    map(
+      preceded(
+        multispace0,
+        preceded(
+          pair(tag("const"), space1),
+          tuple((
+            unannotated_type,
+            identifier,
+            preceded(preceded(multispace0, tag("=")), const_value),
+            opt(annotations),
+            opt(preceded(space0, one_of(",;"))),
+          )),
+        ),
+      ),
+      |v| ConstDefinition {
+        name: v.1,
+        field_type: v.0,
+        value: v.2,
+        comments: vec![],
+        annotations: v.3,
+      },
+    ),
+  )(i)
+}
+
+fn const_definition(i: &str) -> IResult<&str, ConstDefinition> {
+  context(
+    "const_definition",
+    map(
+      preceded(
+        multispace0,
+        tuple((many0(comment), const_definition_without_comments)),
+      ),
+      |v| ConstDefinition {
+        comments: v.0,
+        ..v.1
+      },
+    ),
+  )(i)
+}
+
 fn thrift_document(i: &str) -> IResult<&str, ThriftDocument> {
   context(
     "thrift_document",
     map(
-      many0(alt((
-        map(namespace_definition, |v| TopDefinition::Namespace(v)),
-        map(include_definition, |v| TopDefinition::Include(v)),
-        map(struct_definition, |v| TopDefinition::Struct(v)),
-        map(enum_definition, |v| TopDefinition::Enum(v)),
-        map(service_definition, |v| TopDefinition::Service(v)),
-      ))),
+      terminated(
+        many0(alt((
+          map(namespace_definition, TopDefinition::Namespace),
+          map(include_definition, TopDefinition::Include),
+          map(struct_definition, TopDefinition::Struct),
+          map(exception_definition, TopDefinition::Exception),
+          map(union_definition, TopDefinition::Union),
+          map(enum_definition, TopDefinition::Enum),
+          map(senum_definition, TopDefinition::Senum),
+          map(service_definition, TopDefinition::Service),
+          map(typedef_definition, TopDefinition::Typedef),
+          map(const_definition, TopDefinition::Const),
+        ))),
+        // 文件末尾没有对应定义的注释直接丢弃
+        many0(comment),
+      ),
       |v| ThriftDocument { body: v },
     ),
   )(i)
@@ -702,8 +1651,24 @@ impl<'a> Parser<'a> {
     Self { code }
   }
 
+  /// 使用默认选项解析，`list`、`map` 容器类型以及常量中的 list 和 map 最多嵌套 `MAX_TYPE_DEPTH` 层，超出时返回错误
   pub fn parse(&self) -> Result<ThriftDocument, String> {
-    let (left, ret) = thrift_document(self.code).unwrap();
+    self.parse_with_options(ParseOptions::default())
+  }
+
+  pub fn parse_with_options(&self, options: ParseOptions) -> Result<ThriftDocument, String> {
+    let ret = {
+      let _guard = ParseOptionsGuard::new(options);
+      thrift_document(self.code)
+    };
+
+    let (left, ret) = match ret {
+      Ok(ret) => ret,
+      Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+        return Err(convert_error(self.code, err));
+      }
+      Err(nom::Err::Incomplete(_)) => return Err("Unexpected end of input".to_string()),
+    };
 
     if !left.trim().is_empty() {
       return Err(format!("Unexpected code: {:#?}", left));
@@ -753,6 +1718,34 @@ mod tests {
     assert_eq!(ret.value, 1);
   }
 
+  #[test]
+  fn test_field_id_colon_spacing() {
+    let fields = ["1: i32 x", "1 : i32 x", "1:i32 x", "1 :i32 x"]
+      .iter()
+      .map(|code| field_definition(code).unwrap().1)
+      .collect::<Vec<_>>();
+
+    for field in &fields {
+      assert_eq!(field.field_id.value, 1);
+      assert_eq!(field.field_type, ThriftType::I32);
+      assert_eq!(field.name.value, "x");
+      assert!(field.requiredness.is_none());
+    }
+
+    let (_, ret) = field_definition("2 :optional i32 y").unwrap();
+    assert_eq!(ret.field_id.value, 2);
+    assert_eq!(ret.requiredness.unwrap(), Requiredness::Optional);
+
+    let (_, ret) = field_definition("3:optionalType z").unwrap();
+    assert!(ret.requiredness.is_none());
+    assert_eq!(
+      ret.field_type,
+      ThriftType::Identifier(Identifier {
+        value: "optionalType".to_string()
+      })
+    );
+  }
+
   #[test]
   fn test_requiredness() {
     let (_, ret) = requiredness(" optional").unwrap();
@@ -799,6 +1792,35 @@ mod tests {
     assert_eq!(ret.annotations[1].value.value, "thing");
   }
 
+  #[test]
+  fn test_annotation_literal_tab() {
+    let (_, ret) = field_definition("1:\ti32\tx\t(go.tag = \"json:x\tdb:x\", a=\"\t\")").unwrap();
+    let annotations = &ret.annotations.as_ref().unwrap().annotations;
+
+    assert_eq!(ret.field_type, ThriftType::I32);
+    assert_eq!(ret.name.value, "x");
+    assert_eq!(annotations[0].value.value, "json:x\tdb:x");
+    assert_eq!(annotations[1].value.value, "\t");
+
+    let (_, ret) = const_definition("const string SEP = \"a\t b\"").unwrap();
+    assert_eq!(
+      ret.value,
+      ConstValue::String(StringLiteral {
+        value: "a\t b".to_string()
+      })
+    );
+  }
+
+  #[test]
+  fn test_const_definition_annotations() {
+    let (_, ret) = const_definition("const i32 RED = 1 (enum_group = \"Color\");").unwrap();
+    let annotations = ret.annotations.unwrap().annotations;
+
+    assert_eq!(ret.value, ConstValue::Integer(1));
+    assert_eq!(annotations[0].name.value, "enum_group");
+    assert_eq!(annotations[0].value.value, "Color");
+  }
+
   #[test]
   fn test_thrift_type() {
     let (_, ret) = thrift_type("string").unwrap();
@@ -847,6 +1869,111 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_field_definition_default_value() {
+    let (_, ret) = field_definition("2: optional i32 status = 0 (api.query=\"q\")").unwrap();
+    assert_eq!(ret.field_id.value, 2);
+    assert_eq!(ret.requiredness.as_ref().unwrap(), &Requiredness::Optional);
+    assert_eq!(ret.field_type, ThriftType::I32);
+    assert_eq!(ret.name.value, "status");
+    assert_eq!(ret.default_value, Some(ConstValue::Integer(0)));
+    assert_eq!(ret.annotations.as_ref().unwrap().annotations.len(), 1);
+    assert_eq!(
+      ret.annotations.as_ref().unwrap().annotations[0].name.value,
+      "api.query"
+    );
+    assert_eq!(
+      ret.annotations.as_ref().unwrap().annotations[0].value.value,
+      "q"
+    );
+    assert_eq!(
+      ret.to_thrift(),
+      "2: optional i32 status = 0 (api.query=\"q\")"
+    );
+
+    let (_, ret) = field_definition("1: list<string> tags = [\"a\", \"b\"]").unwrap();
+    assert_eq!(ret.to_thrift(), "1: list<string> tags = [\"a\", \"b\"]");
+  }
+
+  #[test]
+  fn test_field_and_type_annotations() {
+    let (_, ret) = field_definition(
+      "1: list<i32 (a = \"1\")> (cpp.type = \"std::vector\") &x (api.query = \"q\")",
+    )
+    .unwrap();
+
+    assert_eq!(ret.field_type, ThriftType::List(Box::new(ThriftType::I32)));
+    assert!(ret.reference);
+    assert_eq!(ret.name.value, "x");
+
+    let type_annotations = ret.type_annotations.as_ref().unwrap();
+    assert_eq!(type_annotations.annotations.len(), 1);
+    assert_eq!(type_annotations.annotations[0].name.value, "cpp.type");
+    assert_eq!(type_annotations.annotations[0].value.value, "std::vector");
+
+    let annotations = ret.annotations.as_ref().unwrap();
+    assert_eq!(annotations.annotations.len(), 1);
+    assert_eq!(annotations.annotations[0].name.value, "api.query");
+
+    assert_eq!(
+      ret.to_thrift(),
+      "1: list<i32> (cpp.type=\"std::vector\") &x (api.query=\"q\")"
+    );
+
+    let (_, ret) = field_definition("1: i32 x (a = \"1\")").unwrap();
+    assert!(!ret.reference);
+    assert!(ret.type_annotations.is_none());
+    assert!(ret.annotations.is_some());
+
+    let (_, ret) = typedef_definition("typedef map<string, i32> (cpp.template = \"x\") M").unwrap();
+    assert_eq!(ret.name.value, "M");
+  }
+
+  #[test]
+  fn test_field_definition_to_thrift() {
+    let (_, ret) =
+      field_definition("/* 这是多行注释 */\n2: i32 status (api.query=\"query_status\")").unwrap();
+    assert_eq!(
+      ret.to_thrift(),
+      "/* 这是多行注释 */\n2: i32 status (api.query=\"query_status\")"
+    );
+
+    let (_, ret) = field_definition("2: optional map<a.A, list<string>> kvs").unwrap();
+    assert_eq!(ret.to_thrift(), "2: optional map<a.A, list<string>> kvs");
+  }
+
+  #[test]
+  fn test_function_definition_to_thrift() {
+    let (_, ret) = function_definition(
+      "// 获取数据\nGetDataRes GetData(1: GetDataReq req) (api.get = \"/api/get-data\", other = \"something\")",
+    )
+    .unwrap();
+    assert_eq!(
+      ret.to_thrift(),
+      "// 获取数据\nGetDataRes GetData(1: GetDataReq req) (api.get=\"/api/get-data\", other=\"something\")"
+    );
+
+    let (_, ret) = function_definition(
+      "void Ping(1: i32 a, 2: i32 b) throws (1: NotFound e) (other = \"x\", api.get = \"/ping\")",
+    )
+    .unwrap();
+    assert_eq!(
+      ret.to_thrift(),
+      "void Ping(1: i32 a, 2: i32 b) throws (1: NotFound e) (other=\"x\", api.get=\"/ping\")"
+    );
+
+    let (_, ret) = function_definition("oneway void Notify(1: Event e)").unwrap();
+    assert!(ret.oneway);
+    assert_eq!(ret.return_type, ThriftType::Void);
+    assert_eq!(ret.to_thrift(), "oneway void Notify(1: Event e)");
+
+    let (_, ret) = function_definition("onewayResult Get()").unwrap();
+    assert!(!ret.oneway);
+
+    let (_, ret) = field_definition("1: i32 x (api.query=\"q\", other=\"x\")").unwrap();
+    assert_eq!(ret.to_thrift(), "1: i32 x (api.query=\"q\", other=\"x\")");
+  }
+
   #[test]
   fn test_struct_definition() {
     let (_, ret) = struct_definition(
@@ -895,6 +2022,32 @@ struct GetDataReq {
     println!("{:?}", ret);
   }
 
+  #[test]
+  fn test_enum_member_trailing_comment() {
+    for code in [
+      "Normal = 1, // 普通",
+      "Normal = 1 // 普通",
+      "Normal = 1 , // 普通",
+      "Normal = 1; // 普通",
+    ] {
+      let (_, ret) = enum_member(code).unwrap();
+
+      assert_eq!(ret.name.value, "Normal");
+      assert_eq!(ret.initializer.as_ref().unwrap().value, "1");
+      assert_eq!(ret.comments.len(), 1);
+      assert_eq!(ret.comments[0].line_value(), "普通");
+    }
+  }
+
+  #[test]
+  fn test_enum_member_values() {
+    let (_, ret) = enum_definition("enum A { X, Y = 5, Z }").unwrap();
+    assert_eq!(ret.member_values(), [0, 5, 6]);
+
+    let (_, ret) = enum_definition("enum E {\n  A = 9223372036854775807\n  B\n  C = 1\n}").unwrap();
+    assert_eq!(ret.member_values(), [i64::MAX]);
+  }
+
   #[test]
   fn test_enum_definition() {
     let (_, ret) = enum_definition(
@@ -935,6 +2088,29 @@ enum ItemType {
     println!("{:?}", ret);
   }
 
+  #[test]
+  fn test_function_definition_optional_arg() {
+    let (_, ret) = function_definition("GetDataRes GetData(1: optional GetDataReq req)").unwrap();
+
+    assert_eq!(ret.fields.len(), 1);
+    assert_eq!(ret.fields[0].name.value, "req");
+    assert_eq!(
+      ret.fields[0].requiredness.as_ref().unwrap(),
+      &Requiredness::Optional
+    );
+  }
+
+  #[test]
+  fn test_function_definition_without_args() {
+    let (_, ret) = function_definition("GetDataRes Ping()").unwrap();
+    assert_eq!(ret.name.value, "Ping");
+    assert!(ret.fields.is_empty());
+
+    let (_, ret) = function_definition("void Ping( )").unwrap();
+    assert_eq!(ret.return_type, ThriftType::Void);
+    assert!(ret.fields.is_empty());
+  }
+
   #[test]
   fn test_service_definition() {
     let (_, ret) = service_definition(
@@ -980,6 +2156,45 @@ service ThriftService {
     );
   }
 
+  #[test]
+  fn test_typedef_definition() {
+    let (_, ret) = typedef_definition("// 这是 typedef 注释\ntypedef list<a.A> AList").unwrap();
+    assert_eq!(ret.comments.len(), 1);
+    assert_eq!(ret.name.value, "AList");
+    assert_eq!(
+      ret.field_type,
+      ThriftType::List(Box::new(ThriftType::Identifier(Identifier {
+        value: "a.A".to_string()
+      })))
+    );
+  }
+
+  #[test]
+  fn test_resolve_typedef_chain() {
+    let document = Parser::new(
+      r#"
+typedef i64 A
+typedef A B
+typedef map<string, B> C
+"#,
+    )
+    .parse()
+    .unwrap();
+
+    let b = ThriftType::Identifier(Identifier {
+      value: "B".to_string(),
+    });
+    assert_eq!(b.resolve(&document), ThriftType::I64);
+
+    let c = ThriftType::Identifier(Identifier {
+      value: "C".to_string(),
+    });
+    assert_eq!(
+      c.resolve(&document),
+      ThriftType::Map(Box::new(ThriftType::String), Box::new(ThriftType::I64))
+    );
+  }
+
   #[test]
   fn test_thrift_document() {
     let ret = thrift_document(
@@ -1024,4 +2239,471 @@ service ThriftService {
 
     println!("{:?}", ret.0);
   }
+
+  #[test]
+  fn test_parse_with_options() {
+    let code = "include 'a.thrift'\nenum E {\n  A = 1_000\n}";
+
+    // 默认允许单引号，但不允许数字分隔符
+    let ret = Parser::new(code).parse().unwrap();
+    let TopDefinition::Enum(enum_definition) = &ret.body[1] else {
+      panic!("expected enum");
+    };
+    assert_eq!(enum_definition.member_values()[0], 1);
+
+    let ret = Parser::new(code)
+      .parse_with_options(ParseOptions {
+        underscore_separators: true,
+        ..Default::default()
+      })
+      .unwrap();
+    let TopDefinition::Enum(enum_definition) = &ret.body[1] else {
+      panic!("expected enum");
+    };
+    assert_eq!(enum_definition.member_values(), [1000]);
+
+    let ret = Parser::new(code).parse_with_options(ParseOptions {
+      single_quotes: false,
+      underscore_separators: true,
+    });
+    assert!(ret.is_err());
+
+    // 解析结束后恢复默认选项
+    assert_eq!(parse_options(), ParseOptions::default());
+  }
+
+  #[test]
+  fn test_const_integer_range() {
+    let ret = Parser::new(
+      "const i64 BIG = 9223372036854775807\nconst i64 SMALL = -9223372036854775808\nconst i32 MASK = 0xFF;",
+    )
+    .parse()
+    .unwrap();
+
+    let values = ret
+      .body
+      .iter()
+      .map(|definition| match definition {
+        TopDefinition::Const(v) => v.value.clone(),
+        _ => panic!("expected const"),
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      values,
+      [
+        ConstValue::Integer(i64::MAX),
+        ConstValue::Integer(i64::MIN),
+        ConstValue::Integer(255),
+      ]
+    );
+
+    let err = Parser::new("const i64 BIG = 9223372036854775808")
+      .parse()
+      .unwrap_err();
+    assert!(err.contains("integer literal out of i64 range"));
+    assert!(err.contains("line 1"));
+  }
+
+  #[test]
+  fn test_const_values() {
+    let (_, ret) =
+      const_definition(r#"const map<string, list<double>> M = { "a": [1.5, -2e3], 'b': [] }"#)
+        .unwrap();
+
+    assert_eq!(ret.name.value, "M");
+    assert_eq!(
+      ret.value,
+      ConstValue::Map(vec![
+        (
+          ConstValue::String(StringLiteral {
+            value: "a".to_string()
+          }),
+          ConstValue::List(vec![ConstValue::Double(1.5), ConstValue::Double(-2000.0)]),
+        ),
+        (
+          ConstValue::String(StringLiteral {
+            value: "b".to_string()
+          }),
+          ConstValue::List(vec![]),
+        ),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_union_definition() {
+    let (_, ret) =
+      union_definition("// 结果\nunion Result {\n  0: string data\n  1: i32 code\n}").unwrap();
+
+    assert_eq!(ret.name.value, "Result");
+    assert_eq!(ret.comments[0].line_value(), "结果");
+    assert_eq!(
+      ret
+        .fields
+        .iter()
+        .map(|field| (field.field_id.value, field.name.value.as_str()))
+        .collect::<Vec<_>>(),
+      [(0, "data"), (1, "code")]
+    );
+  }
+
+  #[test]
+  fn test_referenced_type_names() {
+    let (_, ret) = struct_definition(
+      r#"
+struct GetDataReq {
+    1: string parameters
+    2: optional map<a.A, string> kvs
+    3: required list<a.A> a_list
+    6: ItemType item_type
+}"#,
+    )
+    .unwrap();
+
+    let names = ret.clone().referenced_type_names();
+    assert_eq!(
+      names,
+      HashSet::from(["a.A".to_string(), "ItemType".to_string()])
+    );
+  }
+
+  #[test]
+  fn test_field_definition_with_comments_inside() {
+    let (left, ret) = field_definition(
+      "1: /* id */ optional /* 类型 */ map<string, i32> // 名称\n kvs /* 注解 */ (a = \"b\") // 行尾\n}",
+    )
+    .unwrap();
+
+    assert_eq!(left, "\n}");
+    assert_eq!(ret.field_id.value, 1);
+    assert_eq!(ret.requiredness, Some(Requiredness::Optional));
+    assert_eq!(
+      ret.field_type,
+      ThriftType::Map(Box::new(ThriftType::String), Box::new(ThriftType::I32))
+    );
+    assert_eq!(ret.name.value, "kvs");
+    assert_eq!(ret.annotations.unwrap().annotations[0].name.value, "a");
+    assert_eq!(ret.comments.len(), 1);
+    assert_eq!(ret.comments[0].line_value(), "行尾");
+
+    let (_, ret) = field_definition("2: i32 /* 无注解 */ x // 行尾").unwrap();
+    assert_eq!(ret.name.value, "x");
+    assert!(ret.annotations.is_none());
+    assert_eq!(ret.comments[0].line_value(), "行尾");
+  }
+
+  #[test]
+  fn test_merge_documents() {
+    let mut document = Parser::new(
+      "namespace ts a\ninclude \"b.thrift\"\nstruct A {\n  1: i32 id\n}\nenum E {\n  X = 1\n}",
+    )
+    .parse()
+    .unwrap();
+    let other = Parser::new(
+      "namespace ts b\ninclude \"b.thrift\"\nstruct B {\n  1: i32 id\n}\ntypedef i32 E",
+    )
+    .parse()
+    .unwrap();
+
+    let collisions = document.merge(other);
+
+    assert_eq!(collisions, ["E"]);
+    assert_eq!(document.body.len(), 5);
+    assert!(matches!(&document.body[4], TopDefinition::Struct(v) if v.name.value == "B"));
+  }
+
+  #[test]
+  fn test_annotation_with_escaped_quotes_and_commas() {
+    let (left, ret) =
+      annotations(r#" (go.tag = "json:\"foo,omitempty\"" , api.query = "a,b", )"#).unwrap();
+
+    assert_eq!(left, "");
+    assert_eq!(ret.annotations.len(), 2);
+    assert_eq!(ret.annotations[0].name.value, "go.tag");
+    assert_eq!(ret.annotations[0].value.value, r#"json:"foo,omitempty""#);
+    assert_eq!(ret.annotations[1].value.value, "a,b");
+  }
+
+  #[test]
+  fn test_definition_annotations() {
+    let (_, ret) =
+      union_definition("union Result {\n  1: string data\n} (ts.discriminator = \"type\")")
+        .unwrap();
+    assert_eq!(ret.annotations.unwrap().annotations[0].value.value, "type");
+
+    let (_, ret) = struct_definition("struct A {\n  1: i32 id\n}\n").unwrap();
+    assert!(ret.annotations.is_none());
+  }
+
+  #[test]
+  fn test_comment_at_end_of_file() {
+    for code in [
+      "struct A {\n  1: i32 id\n}\n// end",
+      "struct A {\n  1: i32 id\n} // end",
+      "struct A {\n  1: i32 id\n}\n/* end */",
+      "// only comment",
+    ] {
+      let ret = Parser::new(code).parse();
+      assert!(ret.is_ok(), "{:?}: {:?}", code, ret);
+    }
+
+    let ret = Parser::new("struct A {\n  1: i32 id\n}\n// end")
+      .parse()
+      .unwrap();
+    assert_eq!(ret.body.len(), 1);
+  }
+
+  #[test]
+  fn test_large_document() {
+    let mut code = "struct Big {\n".to_string();
+    for i in 1..=5000 {
+      code.push_str(&format!(
+        "  {}: optional list<map<string, i64>> field_{}\n",
+        i, i
+      ));
+    }
+    code.push_str("}\nservice BigService {\n");
+    for i in 0..2000 {
+      code.push_str(&format!("  Big Method{}(1: Big req)\n", i));
+    }
+    code.push_str("}\n");
+
+    let ret = Parser::new(&code).parse().unwrap();
+    let TopDefinition::Struct(big) = &ret.body[0] else {
+      panic!("expected struct");
+    };
+    assert_eq!(big.fields.len(), 5000);
+    let TopDefinition::Service(service) = &ret.body[1] else {
+      panic!("expected service");
+    };
+    assert_eq!(service.functions.len(), 2000);
+
+    let nested = |depth: usize| {
+      format!(
+        "typedef {}i32{} T",
+        "list<".repeat(depth),
+        ">".repeat(depth)
+      )
+    };
+    assert!(Parser::new(&nested(MAX_TYPE_DEPTH)).parse().is_ok());
+    let ret = Parser::new(&nested(MAX_TYPE_DEPTH + 1)).parse();
+    assert!(ret.unwrap_err().contains(TYPE_DEPTH_ERROR));
+    let ret = Parser::new(&nested(10000)).parse();
+    assert!(ret.unwrap_err().contains("type nesting too deep"));
+  }
+
+  #[test]
+  fn test_const_value_depth() {
+    let nested = |depth: usize| {
+      format!(
+        "const list<i32> X = {}1{}\nconst map<string, i32> Y = {}1{}",
+        "[".repeat(depth),
+        "]".repeat(depth),
+        "{\"a\": ".repeat(depth),
+        "}".repeat(depth)
+      )
+    };
+    assert!(Parser::new(&nested(MAX_TYPE_DEPTH)).parse().is_ok());
+    let ret = Parser::new(&nested(MAX_TYPE_DEPTH + 1)).parse();
+    assert!(ret.unwrap_err().contains(CONST_DEPTH_ERROR));
+    let ret = Parser::new(&nested(200_000)).parse();
+    assert!(ret.unwrap_err().contains(CONST_DEPTH_ERROR));
+  }
+
+  #[test]
+  fn test_container_edge_cases() {
+    let field_type = |code: &str| thrift_type(code).unwrap().1;
+
+    assert_eq!(
+      field_type("map < string , string >"),
+      ThriftType::Map(Box::new(ThriftType::String), Box::new(ThriftType::String))
+    );
+    assert_eq!(
+      field_type("list<list<i32>>"),
+      ThriftType::List(Box::new(ThriftType::List(Box::new(ThriftType::I32))))
+    );
+    assert_eq!(
+      field_type("map<string,list<map<i32,i64>>>"),
+      ThriftType::Map(
+        Box::new(ThriftType::String),
+        Box::new(ThriftType::List(Box::new(ThriftType::Map(
+          Box::new(ThriftType::I32),
+          Box::new(ThriftType::I64)
+        ))))
+      )
+    );
+    assert_eq!(
+      field_type(r#"list<i32 (cpp.type = "int")> cpp_type "std::vector<int>" (a = "1")"#),
+      ThriftType::List(Box::new(ThriftType::I32))
+    );
+
+    let ret = Parser::new(
+      r#"struct A {
+  1: list < list<string>> (cpp.template = "std::deque") names (go.tag = "json:\"names\"")
+}"#,
+    )
+    .parse()
+    .unwrap();
+    let TopDefinition::Struct(definition) = &ret.body[0] else {
+      panic!("expected struct");
+    };
+    let field = &definition.fields[0];
+    assert_eq!(field.name.value, "names");
+    assert_eq!(
+      field.field_type,
+      ThriftType::List(Box::new(ThriftType::List(Box::new(ThriftType::String))))
+    );
+    assert_eq!(field.annotations.as_ref().unwrap().annotations.len(), 1);
+  }
+
+  #[test]
+  fn test_include_order() {
+    let ret = Parser::new(
+      "include \"b.thrift\"\ninclude \"a.thrift\"\nstruct A {\n  1: a.A a\n}\ninclude \"c.thrift\"",
+    )
+    .parse()
+    .unwrap();
+
+    let paths = ret
+      .body
+      .iter()
+      .filter_map(|definition| match definition {
+        TopDefinition::Include(include) => Some(include.path.value.as_str()),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(paths, ["b.thrift", "a.thrift", "c.thrift"]);
+  }
+
+  #[test]
+  fn test_exception_and_throws() {
+    let ret = Parser::new(
+      r#"exception NotFound {
+  1: string message
+}
+service S {
+  string Get(1: i32 id) throws (
+    1: NotFound not_found
+    2: a.Denied denied
+  ) (api.get = "/get")
+  void Ping()
+}"#,
+    )
+    .parse()
+    .unwrap();
+
+    assert!(matches!(&ret.body[0], TopDefinition::Exception(v) if v.name.value == "NotFound"));
+    assert_eq!(ret.body[0].name(), Some("NotFound"));
+
+    let TopDefinition::Service(service) = &ret.body[1] else {
+      panic!("expected service");
+    };
+    let throws = &service.functions[0].throws;
+    assert_eq!(throws.len(), 2);
+    assert_eq!(throws[1].field_id.value, 2);
+    assert_eq!(throws[1].name.value, "denied");
+    assert!(service.functions[0].annotations.is_some());
+    assert!(service.functions[1].throws.is_empty());
+  }
+
+  #[test]
+  fn test_enum_value_expression() {
+    let ret = Parser::new(
+      "enum Flag {\n  READ = 1 << 0,\n  WRITE = 1 << 1,\n  ALL = 1 | 1<<1 | 1 + 1 << 2\n  NEXT\n}",
+    )
+    .parse()
+    .unwrap();
+    let TopDefinition::Enum(definition) = &ret.body[0] else {
+      panic!("expected enum");
+    };
+
+    assert_eq!(definition.member_values(), [1, 2, 11, 12]);
+    assert_eq!(
+      definition.members[2].initializer.as_ref().unwrap().value,
+      "11"
+    );
+
+    let err = Parser::new("enum Flag {\n  BIG = 1 << 64\n}")
+      .parse()
+      .unwrap_err();
+    assert!(err.contains("enum value out of i64 range"));
+  }
+
+  #[test]
+  fn test_multi_line_function() {
+    let ret = Parser::new(
+      r#"service S {
+  GetDataRes GetData(
+    // 请求
+    1: GetDataReq req, // 行尾注释
+    /* 数量 */
+    2: optional i32 limit;
+    // 结束
+  ) throws (
+    1: NotFound not_found,
+  )
+  // 服务结束
+}
+struct A {
+  1: i32 a, 2: i32 b;
+  // 结构结束
+}"#,
+    )
+    .parse()
+    .unwrap();
+
+    let TopDefinition::Service(service) = &ret.body[0] else {
+      panic!("expected service");
+    };
+    let function = &service.functions[0];
+    let ids = function
+      .fields
+      .iter()
+      .map(|field| (field.field_id.value, field.name.value.as_str()))
+      .collect::<Vec<_>>();
+
+    assert_eq!(ids, [(1, "req"), (2, "limit")]);
+    assert_eq!(function.fields[0].comments.len(), 2);
+    assert_eq!(function.throws.len(), 1);
+
+    let TopDefinition::Struct(definition) = &ret.body[1] else {
+      panic!("expected struct");
+    };
+    assert_eq!(definition.fields.len(), 2);
+  }
+
+  #[test]
+  fn test_senum_definition() {
+    let ret = Parser::new("senum Foo { \"a\" \"b\" \"c\" }")
+      .parse()
+      .unwrap();
+    let TopDefinition::Senum(definition) = &ret.body[0] else {
+      panic!("expected senum");
+    };
+
+    assert_eq!(definition.name.value, "Foo");
+    let values = definition
+      .values
+      .iter()
+      .map(|value| value.value.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(values, ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn test_union_required_member() {
+    let err = Parser::new("union U {\n  1: optional i32 a\n  2: required string b\n}")
+      .parse()
+      .unwrap_err();
+    assert!(err.contains("union members cannot be required"), "{}", err);
+    assert!(err.contains("2: required string b"), "{}", err);
+
+    assert!(
+      Parser::new("union U {\n  1: optional i32 a\n  2: string b\n}")
+        .parse()
+        .is_ok()
+    );
+  }
 }
@@ -0,0 +1,260 @@
+use nom::{
+  branch::alt,
+  bytes::complete::{tag, take_till, take_until, take_while, take_while1},
+  character::complete::{anychar, char, digit1, multispace1, none_of, one_of},
+  combinator::{map, opt, recognize},
+  multi::many0,
+  sequence::{delimited, pair, preceded, tuple},
+  IResult,
+};
+
+const KEYWORDS: &[&str] = &[
+  "namespace",
+  "include",
+  "cpp_include",
+  "typedef",
+  "struct",
+  "union",
+  "exception",
+  "enum",
+  "senum",
+  "const",
+  "service",
+  "extends",
+  "throws",
+  "oneway",
+  "optional",
+  "required",
+  "void",
+  "bool",
+  "byte",
+  "i8",
+  "i16",
+  "i32",
+  "i64",
+  "double",
+  "string",
+  "binary",
+  "list",
+  "set",
+  "map",
+];
+
+/// 词法单元的类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+  /// 关键字，包括 `struct`、`i32` 这类基础类型
+  Keyword,
+  /// 标识符，可以包含 `.`，如 `a.A`
+  Identifier,
+  /// 整数字面量，如 `1`、`-2`、`0x1F`
+  Integer,
+  /// 浮点数字面量，如 `1.5`、`1e3`
+  Double,
+  /// 字符串字面量，`text` 中包含引号
+  String,
+  /// 标点符号，如 `{`、`:`、`=`
+  Punctuation,
+  /// 注释，包括 `//`、`#` 和 `/* */`
+  Comment,
+}
+
+/// 源码中的字节范围，左闭右开
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+}
+
+/// 词法单元，`text` 为源码中对应的原始文本
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+  pub kind: TokenKind,
+  pub span: Span,
+  pub text: String,
+}
+
+fn comment(i: &str) -> IResult<&str, &str> {
+  alt((
+    recognize(pair(tag("//"), take_till(|c| c == '\n' || c == '\r'))),
+    recognize(pair(char('#'), take_till(|c| c == '\n' || c == '\r'))),
+    recognize(delimited(tag("/*"), take_until("*/"), tag("*/"))),
+  ))(i)
+}
+
+fn string(i: &str) -> IResult<&str, &str> {
+  alt((
+    recognize(delimited(
+      char('"'),
+      many0(alt((preceded(char('\\'), anychar), none_of("\"")))),
+      char('"'),
+    )),
+    recognize(delimited(
+      char('\''),
+      many0(alt((preceded(char('\\'), anychar), none_of("'")))),
+      char('\''),
+    )),
+  ))(i)
+}
+
+fn double(i: &str) -> IResult<&str, &str> {
+  alt((
+    recognize(tuple((
+      opt(one_of("+-")),
+      digit1,
+      char('.'),
+      digit1,
+      opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    ))),
+    recognize(tuple((
+      opt(one_of("+-")),
+      digit1,
+      one_of("eE"),
+      opt(one_of("+-")),
+      digit1,
+    ))),
+  ))(i)
+}
+
+fn integer(i: &str) -> IResult<&str, &str> {
+  alt((
+    recognize(pair(
+      alt((tag("0x"), tag("0X"))),
+      take_while1(|c: char| c.is_ascii_hexdigit()),
+    )),
+    recognize(pair(opt(one_of("+-")), digit1)),
+  ))(i)
+}
+
+/// 标识符中的一段，不包含 `.`
+fn word_segment(i: &str) -> IResult<&str, &str> {
+  recognize(pair(
+    take_while1(|c: char| c.is_ascii_alphabetic() || c == '_'),
+    take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+  ))(i)
+}
+
+/// 关键字或标识符，`a.B` 这样带前缀的标识符中每个 `.` 之后都必须跟着一段，不能以 `.` 结尾
+fn word(i: &str) -> IResult<&str, &str> {
+  recognize(pair(word_segment, many0(pair(char('.'), word_segment))))(i)
+}
+
+fn token(i: &str) -> IResult<&str, (TokenKind, &str)> {
+  alt((
+    map(comment, |v| (TokenKind::Comment, v)),
+    map(string, |v| (TokenKind::String, v)),
+    map(double, |v| (TokenKind::Double, v)),
+    map(integer, |v| (TokenKind::Integer, v)),
+    map(word, |v| {
+      if KEYWORDS.contains(&v) {
+        (TokenKind::Keyword, v)
+      } else {
+        (TokenKind::Identifier, v)
+      }
+    }),
+    map(recognize(one_of("{}()<>[],;:=*&")), |v| {
+      (TokenKind::Punctuation, v)
+    }),
+  ))(i)
+}
+
+/// 将 thrift 源码切分为词法单元，空白字符会被跳过
+pub fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = vec![];
+  let mut rest = code;
+
+  loop {
+    if let Ok((left, _)) = multispace1::<&str, nom::error::Error<&str>>(rest) {
+      rest = left;
+    }
+
+    if rest.is_empty() {
+      return Ok(tokens);
+    }
+
+    let start = code.len() - rest.len();
+
+    match token(rest) {
+      Ok((left, (kind, text))) => {
+        tokens.push(Token {
+          kind,
+          span: Span {
+            start,
+            end: start + text.len(),
+          },
+          text: text.to_string(),
+        });
+        rest = left;
+      }
+      Err(_) => {
+        return Err(format!("Unexpected character at {}: {:?}", start, rest));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_tokenize() {
+    let tokens =
+      tokenize("struct A {\n  1: optional a.B x = 1.5, // 注释\n  2: i32 y = -2\n}").unwrap();
+
+    let kinds = tokens
+      .iter()
+      .map(|token| (token.kind, token.text.as_str()))
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      kinds,
+      [
+        (TokenKind::Keyword, "struct"),
+        (TokenKind::Identifier, "A"),
+        (TokenKind::Punctuation, "{"),
+        (TokenKind::Integer, "1"),
+        (TokenKind::Punctuation, ":"),
+        (TokenKind::Keyword, "optional"),
+        (TokenKind::Identifier, "a.B"),
+        (TokenKind::Identifier, "x"),
+        (TokenKind::Punctuation, "="),
+        (TokenKind::Double, "1.5"),
+        (TokenKind::Punctuation, ","),
+        (TokenKind::Comment, "// 注释"),
+        (TokenKind::Integer, "2"),
+        (TokenKind::Punctuation, ":"),
+        (TokenKind::Keyword, "i32"),
+        (TokenKind::Identifier, "y"),
+        (TokenKind::Punctuation, "="),
+        (TokenKind::Integer, "-2"),
+        (TokenKind::Punctuation, "}"),
+      ]
+    );
+
+    assert_eq!(tokens[0].span, Span { start: 0, end: 6 });
+    assert_eq!(tokens[11].text, "// 注释");
+    assert!(tokenize("struct A { @ }").is_err());
+    assert!(tokenize("1: a. x").is_err());
+    assert!(tokenize("1: a..B x").is_err());
+  }
+
+  #[test]
+  fn test_tokenize_string() {
+    let tokens = tokenize(r#"(go.tag = "json:\"a\"")"#).unwrap();
+
+    assert_eq!(tokens[3].kind, TokenKind::String);
+    assert_eq!(tokens[3].text, r#""json:\"a\"""#);
+  }
+
+  #[test]
+  fn test_tokenize_nested_container() {
+    let tokens = tokenize("list<list<i32>>").unwrap();
+    let texts = tokens
+      .iter()
+      .map(|token| token.text.as_str())
+      .collect::<Vec<_>>();
+
+    assert_eq!(texts, ["list", "<", "list", "<", "i32", ">", ">"]);
+  }
+}
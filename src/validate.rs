@@ -0,0 +1,242 @@
+use crate::parse::{
+  Annotations, FieldDefinition, ServiceDefinition, ThriftDocument, TopDefinition,
+};
+
+/// 检查解析结果中语法无法约束的问题，返回所有问题的描述，没有问题时返回空数组
+pub fn validate(document: &ThriftDocument) -> Vec<String> {
+  let mut diagnostics = vec![];
+
+  for definition in &document.body {
+    match definition {
+      TopDefinition::Service(service_definition) => {
+        validate_service(document, service_definition, &mut diagnostics);
+      }
+      TopDefinition::Enum(enum_definition) => {
+        // 隐式编号超出 i64 范围时 `member_values` 会提前截止
+        let len = enum_definition.member_values().len();
+
+        if let Some(member) = enum_definition.members.get(len) {
+          diagnostics.push(format!(
+            "Enum member `{}.{}` has no value, the previous member is already i64::MAX",
+            enum_definition.name.value, member.name.value
+          ));
+        }
+      }
+      _ => {}
+    }
+  }
+
+  diagnostics
+}
+
+/// 检查注解的作用域（第一个 `.` 之前的部分，如 `api.get` 中的 `api`）是否都在 `scopes` 中，
+/// 可以发现 `apii.get` 这种拼写错误
+pub fn validate_annotation_scopes(document: &ThriftDocument, scopes: &[String]) -> Vec<String> {
+  // 带有注解的位置，如 `GetDataReq.status`、`ThriftService.GetData`
+  let mut targets: Vec<(&Option<Annotations>, String)> = vec![];
+
+  for definition in &document.body {
+    match definition {
+      TopDefinition::Struct(v) | TopDefinition::Exception(v) => {
+        targets.extend(field_targets(&v.fields, &v.name.value));
+        targets.push((&v.annotations, v.name.value.clone()));
+      }
+      TopDefinition::Union(v) => {
+        targets.extend(field_targets(&v.fields, &v.name.value));
+        targets.push((&v.annotations, v.name.value.clone()));
+      }
+      TopDefinition::Service(v) => {
+        for function_definition in &v.functions {
+          let location = format!("{}.{}", v.name.value, function_definition.name.value);
+          targets.extend(field_targets(&function_definition.fields, &location));
+          targets.extend(field_targets(&function_definition.throws, &location));
+          targets.push((&function_definition.annotations, location));
+        }
+      }
+      TopDefinition::Const(v) => targets.push((&v.annotations, v.name.value.clone())),
+      _ => {}
+    }
+  }
+
+  let mut diagnostics = vec![];
+
+  for (annotations, location) in targets {
+    for annotation in annotations.iter().flat_map(|v| &v.annotations) {
+      let name = &annotation.name.value;
+      let scope = name.split('.').next().unwrap_or_default();
+
+      if !scopes.iter().any(|v| v == scope) {
+        diagnostics.push(format!(
+          "Unknown annotation scope `{}` in `{}` on `{}`",
+          scope, name, location
+        ));
+      }
+    }
+  }
+
+  diagnostics
+}
+
+/// 字段上带有注解的位置，包括字段本身的注解和紧跟在类型之后的注解
+fn field_targets<'a>(
+  fields: &'a [FieldDefinition],
+  parent: &str,
+) -> Vec<(&'a Option<Annotations>, String)> {
+  fields
+    .iter()
+    .flat_map(|field| {
+      let location = format!("{}.{}", parent, field.name.value);
+      [
+        (&field.type_annotations, location.clone()),
+        (&field.annotations, location),
+      ]
+    })
+    .collect()
+}
+
+fn find_service<'a>(document: &'a ThriftDocument, name: &str) -> Option<&'a ServiceDefinition> {
+  document
+    .body
+    .iter()
+    .find_map(|definition| match definition {
+      TopDefinition::Service(service_definition) if service_definition.name.value == name => {
+        Some(service_definition)
+      }
+      _ => None,
+    })
+}
+
+/// 同一个 service 中函数不能重名，也不能与 `extends` 继承的函数重名
+fn validate_service(
+  document: &ThriftDocument,
+  service_definition: &ServiceDefinition,
+  diagnostics: &mut Vec<String>,
+) {
+  let service_name = &service_definition.name.value;
+  let mut names: Vec<&str> = vec![];
+
+  for function_definition in &service_definition.functions {
+    let name = function_definition.name.value.as_str();
+
+    if names.contains(&name) {
+      diagnostics.push(format!(
+        "Duplicate function `{}` in service `{}`",
+        name, service_name
+      ));
+    } else {
+      names.push(name);
+    }
+  }
+
+  // 引用其他文件中的 service 时无法检查
+  let mut seen = vec![service_name.as_str()];
+  let mut parent = service_definition
+    .extends
+    .as_ref()
+    .and_then(|extends| find_service(document, &extends.value));
+
+  while let Some(parent_definition) = parent {
+    if seen.contains(&parent_definition.name.value.as_str()) {
+      break;
+    }
+
+    seen.push(&parent_definition.name.value);
+
+    for function_definition in &parent_definition.functions {
+      if names.contains(&function_definition.name.value.as_str()) {
+        diagnostics.push(format!(
+          "Function `{}` in service `{}` shadows the one inherited from `{}`",
+          function_definition.name.value, service_name, parent_definition.name.value
+        ));
+      }
+    }
+
+    parent = parent_definition
+      .extends
+      .as_ref()
+      .and_then(|extends| find_service(document, &extends.value));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse::Parser;
+
+  fn validate_code(code: &str) -> Vec<String> {
+    validate(&Parser::new(code).parse().unwrap())
+  }
+
+  #[test]
+  fn test_duplicate_functions() {
+    let diagnostics = validate_code(
+      r#"
+service BaseService {
+    Pong Ping()
+}
+
+service ThriftService extends BaseService {
+    GetDataRes GetData(1: GetDataReq req)
+    GetDataRes GetData(1: GetDataReq req)
+    Pong Ping()
+}
+"#,
+    );
+
+    assert_eq!(
+      diagnostics,
+      [
+        "Duplicate function `GetData` in service `ThriftService`",
+        "Function `Ping` in service `ThriftService` shadows the one inherited from `BaseService`",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_enum_value_overflow() {
+    assert!(validate_code("enum E {\n  A = 9223372036854775807\n}").is_empty());
+    assert_eq!(
+      validate_code("enum E {\n  A = 9223372036854775807\n  B\n}"),
+      ["Enum member `E.B` has no value, the previous member is already i64::MAX"]
+    );
+  }
+
+  #[test]
+  fn test_valid_service() {
+    let diagnostics = validate_code(
+      "service A extends B {\n  void Ping()\n}\nservice B extends A {\n  void Pong()\n}",
+    );
+    assert!(diagnostics.is_empty());
+  }
+
+  #[test]
+  fn test_annotation_scopes() {
+    let document = Parser::new(
+      r#"
+struct GetDataReq {
+    2: i32 status (api.query="query_status")
+    3: list<i32> (cpp.type = "std::vector<int>") ids
+}
+
+service ThriftService {
+    GetDataRes GetData(1: GetDataReq req) (api.get = "/api/get-data", other = "something")
+}
+"#,
+    )
+    .parse()
+    .unwrap();
+
+    assert_eq!(
+      validate_annotation_scopes(&document, &["api".to_string()]),
+      [
+        "Unknown annotation scope `cpp` in `cpp.type` on `GetDataReq.ids`",
+        "Unknown annotation scope `other` in `other` on `ThriftService.GetData`"
+      ]
+    );
+    assert!(validate_annotation_scopes(
+      &document,
+      &["api".to_string(), "cpp".to_string(), "other".to_string()]
+    )
+    .is_empty());
+  }
+}
@@ -1,30 +1,44 @@
 use crate::parse::{
-  EnumDefinition, EnumMember, FieldDefinition, FunctionDefinition, IncludeDefinition,
-  NamespaceDefinition, ServiceDefinition, StructDefinition, ThriftDocument, TopDefinition,
+  ConstDefinition, ConstValue, EnumDefinition, EnumMember, FieldDefinition, FunctionDefinition,
+  IncludeDefinition, NamespaceDefinition, SenumDefinition, ServiceDefinition, StructDefinition,
+  ThriftDocument, TopDefinition, TypedefDefinition, UnionDefinition,
 };
 
 pub trait Visit {
   fn visit_document(&mut self, document: &mut ThriftDocument) {
     for definition in &mut document.body {
-      match definition {
-        TopDefinition::Namespace(namespace_definition) => {
-          self.visit_namespace_definition(namespace_definition)
-        }
-        TopDefinition::Include(include_definition) => {
-          self.visit_include_definition(include_definition)
-        }
-        TopDefinition::Struct(struct_definition) => self.visit_struct_definition(struct_definition),
-        TopDefinition::Enum(enum_definition) => self.visit_enum_definition(enum_definition),
-        TopDefinition::Service(service_definition) => {
-          self.visit_service_definition(service_definition)
-        }
+      self.visit_definition(definition)
+    }
+  }
+
+  fn visit_definition(&mut self, definition: &mut TopDefinition) {
+    match definition {
+      TopDefinition::Namespace(namespace_definition) => {
+        self.visit_namespace_definition(namespace_definition)
+      }
+      TopDefinition::Include(include_definition) => {
+        self.visit_include_definition(include_definition)
+      }
+      TopDefinition::Struct(struct_definition) => self.visit_struct_definition(struct_definition),
+      TopDefinition::Exception(exception_definition) => {
+        self.visit_exception_definition(exception_definition)
+      }
+      TopDefinition::Union(union_definition) => self.visit_union_definition(union_definition),
+      TopDefinition::Enum(enum_definition) => self.visit_enum_definition(enum_definition),
+      TopDefinition::Senum(senum_definition) => self.visit_senum_definition(senum_definition),
+      TopDefinition::Service(service_definition) => {
+        self.visit_service_definition(service_definition)
       }
+      TopDefinition::Typedef(typedef_definition) => {
+        self.visit_typedef_definition(typedef_definition)
+      }
+      TopDefinition::Const(const_definition) => self.visit_const_definition(const_definition),
     }
   }
 
-  fn visit_namespace_definition(&mut self, namespace_definition: &mut NamespaceDefinition) {}
+  fn visit_namespace_definition(&mut self, _namespace_definition: &mut NamespaceDefinition) {}
 
-  fn visit_include_definition(&mut self, include_definition: &mut IncludeDefinition) {}
+  fn visit_include_definition(&mut self, _include_definition: &mut IncludeDefinition) {}
 
   fn visit_struct_definition(&mut self, struct_definition: &mut StructDefinition) {
     for field_definition in &mut struct_definition.fields {
@@ -32,21 +46,130 @@ pub trait Visit {
     }
   }
 
+  /// exception 与 struct 的结构相同，默认同样交给 `visit_struct_definition` 处理
+  fn visit_exception_definition(&mut self, exception_definition: &mut StructDefinition) {
+    self.visit_struct_definition(exception_definition)
+  }
+
+  /// union 的字段与 struct 的字段相同，默认同样交给 `visit_struct_field_definition` 处理
+  fn visit_union_definition(&mut self, union_definition: &mut UnionDefinition) {
+    for field_definition in &mut union_definition.fields {
+      self.visit_struct_field_definition(field_definition)
+    }
+  }
+
   fn visit_enum_definition(&mut self, enum_definition: &mut EnumDefinition) {
     for enum_member in &mut enum_definition.members {
       self.visit_enum_member(enum_member)
     }
   }
 
+  fn visit_senum_definition(&mut self, _senum_definition: &mut SenumDefinition) {}
+
   fn visit_service_definition(&mut self, service_definition: &mut ServiceDefinition) {
     for function_definition in &mut service_definition.functions {
       self.visit_function_definition(function_definition)
     }
   }
 
-  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {}
+  fn visit_typedef_definition(&mut self, _typedef_definition: &mut TypedefDefinition) {}
+
+  fn visit_const_definition(&mut self, const_definition: &mut ConstDefinition) {
+    self.visit_const_value(&mut const_definition.value)
+  }
+
+  /// 默认只访问参数和异常中字段的默认值
+  fn visit_function_definition(&mut self, function_definition: &mut FunctionDefinition) {
+    for field_definition in function_definition
+      .fields
+      .iter_mut()
+      .chain(&mut function_definition.throws)
+    {
+      if let Some(default_value) = &mut field_definition.default_value {
+        self.visit_default_value(default_value)
+      }
+    }
+  }
+
+  fn visit_struct_field_definition(&mut self, field_definition: &mut FieldDefinition) {
+    if let Some(default_value) = &mut field_definition.default_value {
+      self.visit_default_value(default_value)
+    }
+  }
+
+  /// 字段的默认值，默认同样交给 `visit_const_value` 处理
+  fn visit_default_value(&mut self, default_value: &mut ConstValue) {
+    self.visit_const_value(default_value)
+  }
+
+  /// 常量和字段默认值中的每个值，默认通过 `walk_const_value` 继续访问 list 和 map 中的元素
+  fn visit_const_value(&mut self, const_value: &mut ConstValue) {
+    walk_const_value(self, const_value)
+  }
+
+  fn visit_enum_member(&mut self, _enum_member: &mut EnumMember) {}
+}
+
+/// 依次访问 list 和 map 中的元素，覆盖 `visit_const_value` 时可以调用它继续向下访问
+pub fn walk_const_value<V: Visit + ?Sized>(visitor: &mut V, const_value: &mut ConstValue) {
+  match const_value {
+    ConstValue::List(items) => {
+      for item in items {
+        visitor.visit_const_value(item)
+      }
+    }
+    ConstValue::Map(entries) => {
+      for (key, value) in entries {
+        visitor.visit_const_value(key);
+        visitor.visit_const_value(value)
+      }
+    }
+    _ => {}
+  }
+}
 
-  fn visit_struct_field_definition(&mut self, field_definition: &mut FieldDefinition) {}
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse::Parser;
 
-  fn visit_enum_member(&mut self, enum_member: &mut EnumMember) {}
+  struct IntegerCollector {
+    integers: Vec<i64>,
+  }
+
+  impl Visit for IntegerCollector {
+    fn visit_const_value(&mut self, const_value: &mut ConstValue) {
+      if let ConstValue::Integer(v) = const_value {
+        self.integers.push(*v);
+      }
+
+      walk_const_value(self, const_value)
+    }
+  }
+
+  #[test]
+  fn test_visit_const_value() {
+    let mut document = Parser::new(
+      r#"
+const i32 A = 1
+const list<i32> B = [2, 3]
+const map<i32, list<i32>> C = {4: [5], 6: []}
+const string D = "7"
+struct S {
+  1: i32 x = 8
+  2: list<i32> y = [9]
+}
+service Svc {
+  void Get(1: i32 z = 10)
+}
+"#,
+    )
+    .parse()
+    .unwrap();
+
+    let mut collector = IntegerCollector { integers: vec![] };
+    collector.visit_document(&mut document);
+
+    assert_eq!(collector.integers, [1, 2, 3, 4, 5, 6, 8, 9, 10]);
+  }
 }
@@ -0,0 +1,3 @@
+enum B {
+  X = 1
+}
@@ -0,0 +1,2 @@
+include "a.thrift"
+include "b.thrift"
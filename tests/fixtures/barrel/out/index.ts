@@ -0,0 +1,8 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+import * as a from './a';
+export { a };
+import * as b from './b';
+export { b };
@@ -0,0 +1,8 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+
+export enum B {
+  X = 1,
+}
@@ -0,0 +1,60 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+
+export interface B {
+  id: number;
+  some_string: string;
+}
+
+export enum A {
+  /** Some */
+  Some,
+  /** Other */
+  Other,
+}
+
+export enum ItemType {
+  /** 未知 */
+  Unknown = 0,
+  /** 普通 */
+  Normal = 1,
+  /** 特别 */
+  Special = 2,
+}
+
+export interface GetDataReq {
+  /**
+   * 这是单行注释
+   *
+   * 这也是单行注释
+   */
+  parameters: string;
+  /** 这是多行注释 */
+  query_status: number;
+  money: number;
+  is_ok: boolean;
+  kvs?: Partial<Record<A, string>>;
+  a_list: Array<A>;
+  item_type: ItemType;
+}
+
+export interface GetDataRes {
+  body_status: number;
+  msg: string;
+}
+
+type _Options = any;
+
+let urlPrefix = '';
+export function setUrlPrefix(prefix: string) {
+  urlPrefix = prefix;
+}
+
+/** 获取数据 */
+export function GetData(req: GetDataReq, options: _Options): Promise<GetDataRes> {
+  const query = new URLSearchParams(req).toString();
+  const url = `${urlPrefix}/api/get-data?${query}`;
+  return fetch(url, { method: 'GET' }, options);
+}
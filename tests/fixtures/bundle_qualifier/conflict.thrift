@@ -0,0 +1,5 @@
+include "types.thrift"
+
+enum ItemType {
+  Other = 0
+}
@@ -0,0 +1,7 @@
+include "types.thrift"
+
+const types.ItemType DEFAULT_TYPE = types.ItemType.Normal
+
+struct Item {
+  1: types.ItemType item_type = types.ItemType.Unknown
+}
@@ -0,0 +1,15 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+
+export enum ItemType {
+  Unknown = 0,
+  Normal = 1,
+}
+
+export const DEFAULT_TYPE: ItemType = ItemType.Normal;
+
+export interface Item {
+  item_type: ItemType;
+}
@@ -0,0 +1,4 @@
+enum ItemType {
+  Unknown = 0
+  Normal = 1
+}
@@ -0,0 +1,5 @@
+include "../src/sub/types.thrift"
+
+struct Base {
+  1: types.Id id
+}
@@ -0,0 +1,9 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+import * as types from '../../sub/types';
+
+export interface Base {
+  id: types.Id;
+}
@@ -0,0 +1,9 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+import * as base from './_external/common/base';
+
+export interface Main {
+  base: base.Base;
+}
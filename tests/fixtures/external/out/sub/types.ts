@@ -0,0 +1,6 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+
+export type Id = number;
@@ -0,0 +1,5 @@
+include "../common/base.thrift"
+
+struct Main {
+  1: base.Base base
+}
@@ -0,0 +1,5 @@
+include "types.thrift"
+
+struct Main {
+  1: a.A item
+}
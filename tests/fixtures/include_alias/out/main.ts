@@ -0,0 +1,9 @@
+/* eslint-disable */
+// @ts-nocheck
+// This file is auto generated by thrift_parser.
+
+import * as a from './types';
+
+export interface Main {
+  item: a.A;
+}
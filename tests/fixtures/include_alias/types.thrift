@@ -0,0 +1,5 @@
+namespace ts a
+
+struct A {
+  1: i32 id
+}